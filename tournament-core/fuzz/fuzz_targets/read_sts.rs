@@ -0,0 +1,18 @@
+//! Feeds arbitrary bytes to the .sts reader, which has to give an error rather than panic.
+//! Anything it does read has to save and read back the same. Run from `tournament-core` with
+//! `cargo +nightly fuzz run read_sts ../test-files/corpus`
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tournament_core::tournament::Tournament;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(tournament) = Tournament::read_from(data) else {
+        return;
+    };
+
+    let mut written = Vec::new();
+    tournament.write_to(&mut written).unwrap();
+    let read = Tournament::read_from(&written[..]).unwrap();
+    assert_eq!(read, tournament);
+});
@@ -0,0 +1,282 @@
+//! A record of how a round was paired so a pairing can be explained afterwards. Engines
+//! fill in a [`PairingTrace`] as they pair, the tournament adds who it tried to give the
+//! bye to and what was paired in the end.
+
+use std::fmt::Display;
+
+use crate::engine::{PairingEngine, PairingSystem};
+use crate::player::Player;
+use crate::swiss::{Pairing, Points};
+
+/// Everything that went into pairing one round
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PairingAudit {
+    /// the round that was paired, counting from 1
+    pub round: u16,
+    /// the tournament seed, the round number picks the stream of it that was used
+    pub seed: u64,
+    pub system: PairingSystem,
+    /// in the order the engine paired them
+    pub brackets: Vec<ScoreBracket>,
+    pub floats: Vec<Float>,
+    /// pairs the engine passed over because they had already played
+    pub rematches_avoided: Vec<(u16, u16)>,
+    /// rematches that were paired anyway
+    pub rematches: Vec<(u16, u16)>,
+    pub bye: Option<ByeSelection>,
+    /// players sharing a tag who were paired because the round could not be paired otherwise
+    pub relaxed_tags: Vec<(u16, u16)>,
+}
+
+/// What an engine records about a round as it pairs it, see [`PairingEngine::pair`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PairingTrace {
+    /// each group of players the engine paired together, in the order it paired them
+    pub brackets: Vec<ScoreBracket>,
+    pub floats: Vec<Float>,
+    /// pairs the engine would have paired next had they not already played
+    pub rematches_avoided: Vec<(u16, u16)>,
+    /// players the engine tried to give the bye to before the one who got it
+    pub byes_tried: Vec<u16>,
+}
+
+/// Players an engine paired as a group, those on the most points first
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreBracket {
+    pub points: Points,
+    pub players: Vec<u16>,
+}
+
+/// A player paired against someone on fewer points
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Float {
+    pub player: u16,
+    pub opponent: u16,
+    pub from: Points,
+    pub to: Points,
+    pub reason: FloatReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloatReason {
+    /// everyone else in their bracket had already played them or shared their tag
+    NoOpponentInBracket,
+    /// their bracket had an odd number of players
+    OddBracket,
+    /// pairing them in their bracket would have left the rest of the field unpairable
+    PairingTheRest,
+    /// the best pairing of the whole field had them against someone on fewer points
+    BestMatching,
+}
+
+/// Who got the bye and who was passed over for it
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ByeSelection {
+    pub player: u16,
+    /// the pairing engine picked the bye so only who it tried first is passed over
+    pub chosen_by_engine: bool,
+    /// players below the bye in the standings that did not get it
+    pub passed_over: Vec<(u16, ByeSkip)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ByeSkip {
+    HadBye,
+    /// the rest of the field could not be paired with them on the bye
+    NoLegalPairing,
+}
+
+impl TryFrom<u8> for FloatReason {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::NoOpponentInBracket,
+            1 => Self::OddBracket,
+            2 => Self::PairingTheRest,
+            3 => Self::BestMatching,
+            e => return Err(e),
+        })
+    }
+}
+
+impl TryFrom<u8> for ByeSkip {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::HadBye,
+            1 => Self::NoLegalPairing,
+            e => return Err(e),
+        })
+    }
+}
+
+impl PairingAudit {
+    /// takes what the engine recorded and notes the rematches it paired
+    pub(crate) fn read_trace(&mut self, trace: PairingTrace, pairings: &[Pairing]) {
+        self.brackets = trace.brackets;
+        self.floats = trace.floats;
+        self.rematches_avoided = trace.rematches_avoided;
+        self.rematches = pairings
+            .iter()
+            .filter_map(|p| match p.get_players() {
+                (p1, Some(p2)) if p1.has_played(p2.get_number()) => Some((p1.get_number(), p2.get_number())),
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// records who got the bye, `ranked` is every player best first and `failed` the
+    /// players the tournament, or the engine when it picks the bye, tried to give it to first
+    pub(crate) fn read_bye(&mut self, pairings: &[Pairing], ranked: &[&Player], failed: &[u16]) {
+        let Some(bye) = pairings.iter().find(|p| p.is_bye()).map(|p| p.get_players().0) else {
+            return;
+        };
+
+        let chosen_by_engine = self.system.picks_bye();
+        let paired = |number: u16| pairings.iter().any(|p| {
+            let (p1, p2) = p.get_players();
+            p1.get_number() == number || p2.is_some_and(|p2| p2.get_number() == number)
+        });
+
+        let mut passed_over = Vec::new();
+        if chosen_by_engine {
+            passed_over.extend(failed.iter().map(|&number| (number, ByeSkip::NoLegalPairing)));
+        } else {
+            let below = ranked
+                .iter()
+                .rev()
+                .take_while(|p| p.get_number() != bye.get_number())
+                .filter(|p| paired(p.get_number()))
+                .map(|p| (p.get_number(), p.has_had_bye()));
+            for (number, had_bye) in below {
+                if failed.contains(&number) {
+                    passed_over.push((number, ByeSkip::NoLegalPairing));
+                } else if had_bye {
+                    passed_over.push((number, ByeSkip::HadBye));
+                }
+            }
+            // a second bye is only given once every fresh player failed, wherever they stand
+            for &number in failed {
+                if !passed_over.iter().any(|&(n, _)| n == number) {
+                    passed_over.push((number, ByeSkip::NoLegalPairing));
+                }
+            }
+        }
+
+        self.bye = Some(ByeSelection {
+            player: bye.get_number(),
+            chosen_by_engine,
+            passed_over,
+        });
+    }
+}
+
+fn write_pairs(f: &mut std::fmt::Formatter<'_>, title: &str, pairs: &[(u16, u16)]) -> std::fmt::Result {
+    if pairs.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(f, "{}", title)?;
+    for (a, b) in pairs {
+        writeln!(f, "  {} and {}", a, b)?;
+    }
+
+    Ok(())
+}
+
+/// the text export of the audit
+impl Display for PairingAudit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Round {} paired with {} from seed {:016x}", self.round, self.system.name(), self.seed)?;
+
+        writeln!(f, "Score brackets")?;
+        for bracket in &self.brackets {
+            let players = bracket.players.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+            writeln!(f, "  {}: {}", bracket.points, players.join(", "))?;
+        }
+
+        if !self.floats.is_empty() {
+            writeln!(f, "Floats")?;
+            for float in &self.floats {
+                let reason = match float.reason {
+                    FloatReason::NoOpponentInBracket => "no one left in their bracket they could play",
+                    FloatReason::OddBracket => "odd number of players in their bracket",
+                    FloatReason::PairingTheRest => "needed to pair the rest of the field",
+                    FloatReason::BestMatching => "best pairing of the whole field",
+                };
+                writeln!(f, "  {} down from {} to {} against {}, {}", float.player, float.from, float.to, float.opponent, reason)?;
+            }
+        }
+
+        write_pairs(f, "Rematches avoided", &self.rematches_avoided)?;
+        write_pairs(f, "Rematches paired", &self.rematches)?;
+        write_pairs(f, "Paired despite sharing a tag", &self.relaxed_tags)?;
+
+        if let Some(bye) = &self.bye {
+            let chosen = if bye.chosen_by_engine { " chosen by the pairing system" } else { "" };
+            writeln!(f, "Bye to {}{}", bye.player, chosen)?;
+            for (number, skip) in &bye.passed_over {
+                let reason = match skip {
+                    ByeSkip::HadBye => "already had a bye",
+                    ByeSkip::NoLegalPairing => "the rest could not be paired",
+                };
+                writeln!(f, "  passed over {}, {}", number, reason)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swiss::Outcome;
+    use crate::tournament::Tournament;
+
+    #[test]
+    fn rounds_are_explained() {
+        let players = (1..=5).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Audit".to_string(), players);
+        tournament.set_seed(5);
+        tournament.set_auditing(true);
+
+        for _ in 0..2 {
+            tournament.start_round().unwrap();
+            for idx in 0..2 {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        let audits = tournament.get_audits();
+        assert_eq!(audits.len(), 2);
+        let first_bye = audits[0].bye.as_ref().unwrap();
+        assert!(first_bye.passed_over.is_empty());
+
+        let second = &audits[1];
+        assert_eq!(second.round, 2);
+        assert_eq!(second.seed, 5);
+        // the first bye sits on 3 points so it is passed over if it is below the new bye
+        let bye = second.bye.as_ref().unwrap();
+        assert_ne!(bye.player, first_bye.player);
+        let points = second.brackets.iter().map(|b| b.points).collect::<Vec<Points>>();
+        assert_eq!(points, vec![Points::new(3), Points::ZERO]);
+        assert_eq!(second.brackets.iter().map(|b| b.players.len()).sum::<usize>(), 4);
+        assert!(second.rematches.is_empty());
+
+        let report = tournament.audit_report();
+        assert!(report.contains("Round 2 paired with Greedy from seed 0000000000000005"));
+        assert!(report.contains(&format!("Bye to {}", bye.player)));
+
+        // undoing the round takes its audit with it
+        tournament.undo_to(tournament.get_journal().get_events().len() - 4).unwrap();
+        assert_eq!(tournament.get_audits().len(), 1);
+    }
+}
@@ -0,0 +1,213 @@
+//! Keeping a running tournament on disk. An [`Autosave`] given to a tournament writes it out
+//! after every change and before overwriting the last save it can copy it aside, keeping a
+//! few of the newest copies to go back to.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::tournament::{Tournament, TournamentIOError};
+
+/// Where a tournament saves itself to after every change
+#[derive(Clone, Debug, PartialEq)]
+pub struct Autosave {
+    path: PathBuf,
+    /// how many backups are kept, the oldest is deleted when another is made
+    backups: usize,
+    /// the least time between two backups so a busy round does not roll every one away
+    backup_interval: Duration,
+    last_backup: Option<SystemTime>,
+    /// why the last save failed, cleared by the next one to work
+    error: Option<String>,
+    /// why the last backup failed, the save is still written when it does
+    backup_error: Option<String>,
+}
+
+/// A copy of an earlier save
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// when the copy was taken
+    pub time: SystemTime,
+}
+
+impl Autosave {
+    /// saves to `path` without keeping any backups
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            backups: 0,
+            backup_interval: Duration::from_secs(5 * 60),
+            last_backup: None,
+            error: None,
+            backup_error: None,
+        }
+    }
+
+    pub fn with_backups(self, backups: usize) -> Self {
+        Self {
+            backups,
+            ..self
+        }
+    }
+
+    pub fn with_backup_interval(self, backup_interval: Duration) -> Self {
+        Self {
+            backup_interval,
+            ..self
+        }
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get_backup_count(&self) -> usize {
+        self.backups
+    }
+
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn get_backup_error(&self) -> Option<&str> {
+        self.backup_error.as_deref()
+    }
+
+    /// when the newest backup was taken, `None` before the first
+    pub fn get_last_backup(&self) -> Option<SystemTime> {
+        self.last_backup
+    }
+
+    /// writes the tournament out, backing up the last save first when one is due. The
+    /// save is written whether or not the backup works
+    pub fn save(&mut self, tournament: &Tournament) {
+        self.backup_error = self.back_up().err().map(|e| e.to_string());
+        self.error = tournament.write_to_file(&self.path).err().map(|e| e.to_string());
+    }
+
+    /// every backup of this save still on disk, newest first
+    pub fn list_backups(&self) -> std::io::Result<Vec<Backup>> {
+        let Some(file_name) = self.path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(Vec::new());
+        };
+        let prefix = format!("{}.", file_name);
+
+        let mut backups = Vec::new();
+        for entry in std::fs::read_dir(self.directory())? {
+            let path = entry?.path();
+            let millis = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(&prefix))
+                .and_then(|n| n.strip_suffix(".bak"))
+                .and_then(|n| n.parse::<u64>().ok());
+            if let Some(millis) = millis {
+                backups.push(Backup {
+                    path,
+                    time: UNIX_EPOCH + Duration::from_millis(millis),
+                });
+            }
+        }
+        backups.sort_by_key(|b| std::cmp::Reverse(b.time));
+
+        Ok(backups)
+    }
+
+    fn directory(&self) -> &Path {
+        match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        }
+    }
+
+    fn back_up(&mut self) -> std::io::Result<()> {
+        let now = SystemTime::now();
+        let due = self
+            .last_backup
+            .is_none_or(|last| now.duration_since(last).unwrap_or_default() >= self.backup_interval);
+        if self.backups == 0 || !due || !self.path.exists() {
+            return Ok(());
+        }
+
+        let millis = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let mut backup = self.path.clone().into_os_string();
+        backup.push(format!(".{}.bak", millis));
+        std::fs::copy(&self.path, backup)?;
+        self.last_backup = Some(now);
+
+        for old in self.list_backups()?.into_iter().skip(self.backups) {
+            std::fs::remove_file(old.path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Backup {
+    pub fn restore(&self) -> Result<Tournament, TournamentIOError> {
+        Tournament::read_from_file(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::Player;
+
+    #[test]
+    fn backups_roll_over() {
+        let directory = std::env::temp_dir().join("tcg_swiss_backups_roll_over");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir(&directory).unwrap();
+        let path = directory.join("event.sts");
+
+        let mut tournament = Tournament::new("Autosaved".to_string(), Vec::new());
+        tournament.set_autosave(Some(Autosave::new(&path).with_backups(2).with_backup_interval(Duration::ZERO)));
+        for n in 1..=4 {
+            tournament.add_player(Player::new(n.to_string(), n));
+            // backups are named to the millisecond
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(tournament.get_autosave().unwrap().get_error(), None);
+        assert_eq!(Tournament::read_from_file(&path).unwrap(), tournament);
+
+        let backups = tournament.get_autosave().unwrap().list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        // the newest backup is the save from before the last player was added
+        let restored = backups[0].restore().unwrap();
+        assert_eq!(restored.iter_all_players().count(), 3);
+        let mut older = backups[1].restore().unwrap();
+        assert_eq!(older.iter_all_players().count(), 2);
+
+        // undoing is a change like any other
+        tournament.undo().unwrap();
+        assert_eq!(Tournament::read_from_file(&path).unwrap(), restored);
+
+        // a restored backup taking over the autosave is written out at once
+        older.set_autosave(tournament.get_autosave().cloned());
+        assert_eq!(Tournament::read_from_file(&path).unwrap(), older);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn failed_backups_still_save() {
+        let directory = std::env::temp_dir().join("tcg_swiss_failed_backups_still_save");
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir(&directory).unwrap();
+        let path = directory.join("event.sts");
+        // looks like the oldest backup but can not be removed like one
+        std::fs::create_dir(directory.join("event.sts.1.bak")).unwrap();
+
+        let mut tournament = Tournament::new("Autosaved".to_string(), Vec::new());
+        tournament.set_autosave(Some(Autosave::new(&path).with_backups(1).with_backup_interval(Duration::ZERO)));
+        tournament.add_player(Player::new("1".to_string(), 1));
+
+        let autosave = tournament.get_autosave().unwrap();
+        assert!(autosave.get_backup_error().is_some());
+        assert_eq!(autosave.get_error(), None);
+        assert_eq!(Tournament::read_from_file(&path).unwrap(), tournament);
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,720 @@
+//! Pairing following the FIDE Dutch system (C.04.3). Players are ranked by score then
+//! player number, which is used as the pairing number. Each score bracket is split into
+//! a top half S1 and bottom half S2, S1 is paired against S2 trying transpositions of S2
+//! then exchanges between the halves until the absolute criteria are met and as many
+//! colour preferences as possible are granted. Players that can not be paired float down
+//! into the next bracket, the floaters of the penultimate bracket are chosen so the last
+//! bracket can be completed and if it still can not be the two are joined together.
+//!
+//! The absolute criteria are no rematches, no second bye and never pairing two players
+//! that both have an absolute preference for the same colour, along with keeping apart
+//! players sharing a tag when the context asks for it. The topscorer exception
+//! for the final round is not applied as the engine does not know how many rounds remain.
+
+use std::cmp::Reverse;
+
+use rand::RngCore;
+
+use crate::audit::{Float, FloatReason, PairingTrace, ScoreBracket};
+use crate::engine::{PairingContext, PairingEngine};
+use crate::player::Player;
+use crate::swiss::{Colour, Pairing, PairingError, Points, RematchPolicy};
+
+/// colour the top seed receives in round one
+const INITIAL_COLOUR: Colour = Colour::White;
+
+/// stops the search from trying every permutation of a large field
+const SEARCH_BUDGET: usize = 200_000;
+
+pub struct DutchEngine;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Strength {
+    None,
+    Mild,
+    Strong,
+    Absolute,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Preference {
+    colour: Option<Colour>,
+    strength: Strength,
+}
+
+fn preference(player: &Player) -> Preference {
+    let colours = player.get_colours();
+    let Some(&last) = colours.last() else {
+        return Preference {
+            colour: None,
+            strength: Strength::None,
+        };
+    };
+
+    let difference = player.colour_balance();
+    let repeated = colours.len() >= 2 && colours[colours.len() - 2] == last;
+
+    let (colour, strength) = match difference {
+        d if d > 1 => (Colour::Black, Strength::Absolute),
+        d if d < -1 => (Colour::White, Strength::Absolute),
+        _ if repeated => (!last, Strength::Absolute),
+        1 => (Colour::Black, Strength::Strong),
+        -1 => (Colour::White, Strength::Strong),
+        _ => (!last, Strength::Mild),
+    };
+
+    Preference {
+        colour: Some(colour),
+        strength,
+    }
+}
+
+struct Entrant<'a> {
+    player: &'a Player,
+    points: Points,
+    preference: Preference,
+}
+
+/// receives a candidate pairing of a bracket and who is left over
+type Accept<'f, 'a> = &'f mut dyn FnMut(&mut Field<'a>, &[(usize, usize)], Vec<usize>) -> bool;
+
+struct Field<'a> {
+    /// every entrant in rank order, entrants are referred to by their rank
+    entrants: Vec<Entrant<'a>>,
+    rematches: bool,
+    /// keeps apart players sharing a tag
+    context: PairingContext<'a>,
+    budget: usize,
+    /// each bracket paired so far, how many were moved down into it and who was left over
+    paired_brackets: Vec<(Vec<usize>, usize, Vec<usize>)>,
+}
+
+impl<'a> Field<'a> {
+    /// true if the pair meets every absolute criterion and does not break colour
+    /// preferences stronger than `tolerance`
+    fn allowed(&self, a: usize, b: usize, tolerance: Strength) -> bool {
+        let (a, b) = (&self.entrants[a], &self.entrants[b]);
+        if !self.rematches && a.player.has_played(b.player.get_number()) {
+            return false;
+        }
+
+        if !self.context.tags_allow(a.player, b.player) {
+            return false;
+        }
+
+        let (pa, pb) = (a.preference, b.preference);
+        if pa.colour.is_none() || pa.colour != pb.colour {
+            return true;
+        }
+
+        let weaker = Strength::min(pa.strength, pb.strength);
+        weaker != Strength::Absolute && weaker <= tolerance
+    }
+
+    /// splits the field into score brackets and pairs them from the top, joining the
+    /// lowest brackets together until everyone can be paired
+    fn pair(&mut self) -> Option<Vec<(usize, usize)>> {
+        let mut brackets: Vec<Vec<usize>> = Vec::new();
+        for rank in 0..self.entrants.len() {
+            match brackets.last_mut() {
+                Some(b) if self.entrants[b[0]].points == self.entrants[rank].points => b.push(rank),
+                _ => brackets.push(vec![rank]),
+            }
+        }
+
+        for joined_from in (0..brackets.len()).rev() {
+            let mut groups = brackets[..joined_from].to_vec();
+            groups.push(brackets[joined_from..].concat());
+
+            self.budget = SEARCH_BUDGET;
+            if let Some(pairs) = self.pair_brackets(&groups) {
+                return Some(pairs);
+            }
+        }
+
+        None
+    }
+
+    /// pairs each bracket from the top taking its best candidate. The penultimate
+    /// bracket only takes a candidate whose floaters let the last bracket be completed
+    fn pair_brackets(&mut self, groups: &[Vec<usize>]) -> Option<Vec<(usize, usize)>> {
+        let mut pairs = Vec::new();
+        let mut floaters = Vec::new();
+        self.paired_brackets.clear();
+
+        for (idx, group) in groups.iter().enumerate() {
+            let moved_down = floaters.len();
+            let mut bracket = std::mem::take(&mut floaters);
+            bracket.extend_from_slice(group);
+
+            let last = idx + 1 == groups.len();
+            let next_is_last = idx + 2 == groups.len();
+            let mut chosen = None;
+            self.pair_bracket(&bracket, moved_down, last, &mut |field, bracket_pairs, left| {
+                if next_is_last && !field.can_complete(&left, &groups[idx + 1]) {
+                    return false;
+                }
+                chosen = Some((bracket_pairs.to_vec(), left));
+                true
+            });
+
+            let (bracket_pairs, left) = chosen?;
+            pairs.extend(bracket_pairs);
+            self.paired_brackets.push((bracket, moved_down, left.clone()));
+            floaters = left;
+        }
+
+        Some(pairs)
+    }
+
+    fn can_complete(&mut self, floaters: &[usize], group: &[usize]) -> bool {
+        let mut bracket = floaters.to_vec();
+        bracket.extend_from_slice(group);
+        self.pair_bracket(&bracket, floaters.len(), true, &mut |_, _, _| true)
+    }
+
+    /// offers `accept` every candidate pairing of the bracket, most pairs then fewest
+    /// colour preferences broken first. `accept` returns true to stop the search
+    fn pair_bracket(&mut self, bracket: &[usize], moved_down: usize, complete: bool, accept: Accept<'_, 'a>) -> bool {
+        let most_pairs = bracket.len() / 2;
+
+        for target in (0..=most_pairs).rev() {
+            if complete && target * 2 != bracket.len() {
+                continue;
+            }
+
+            for tolerance in [Strength::None, Strength::Mild, Strength::Strong] {
+                if self.try_bracket(bracket, moved_down, target, tolerance, accept) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// in a bracket with players moved down from above those players are paired first
+    /// against the residents then the remainder is paired on its own
+    fn try_bracket(&mut self, bracket: &[usize], moved_down: usize, target: usize, tolerance: Strength, accept: Accept<'_, 'a>) -> bool {
+        let residents = bracket.len() - moved_down;
+        if moved_down == 0 || residents == 0 {
+            return self.try_homogeneous(bracket, target, tolerance, accept);
+        }
+
+        let paired_down = moved_down.min(residents).min(target);
+        let s1 = &bracket[..paired_down];
+        let s2 = &bracket[moved_down..];
+
+        self.assign(s1, s2, tolerance, &mut Vec::new(), &mut vec![false; s2.len()], &mut |field, main, used| {
+            let mut remainder = bracket[paired_down..moved_down].to_vec();
+            remainder.extend(s2.iter().zip(used).filter(|(_, u)| !**u).map(|(&r, _)| r));
+
+            field.try_homogeneous(&remainder, target - paired_down, tolerance, &mut |field, rest, left| {
+                let mut all = main.to_vec();
+                all.extend_from_slice(rest);
+                accept(field, &all, left)
+            })
+        })
+    }
+
+    /// pairs the top `target` players (S1) against the rest (S2), trying S2 in
+    /// transposition order and then single exchanges between S1 and S2
+    fn try_homogeneous(&mut self, bracket: &[usize], target: usize, tolerance: Strength, accept: Accept<'_, 'a>) -> bool {
+        if target == 0 {
+            return accept(self, &[], bracket.to_vec());
+        }
+
+        let (s1, s2) = bracket.split_at(target);
+        let mut splits = vec![(s1.to_vec(), s2.to_vec())];
+
+        // exchanges closest in rank first, lowest of S1 with highest of S2
+        let mut exchanges = Vec::new();
+        for (i, &low) in s1.iter().enumerate().rev() {
+            for (j, &high) in s2.iter().enumerate() {
+                exchanges.push((high - low, Reverse(low), i, j));
+            }
+        }
+        exchanges.sort();
+        for (_, _, i, j) in exchanges {
+            let (mut s1, mut s2) = (s1.to_vec(), s2.to_vec());
+            std::mem::swap(&mut s1[i], &mut s2[j]);
+            s1.sort();
+            s2.sort();
+            splits.push((s1, s2));
+        }
+
+        for (s1, s2) in splits {
+            let found = self.assign(&s1, &s2, tolerance, &mut Vec::new(), &mut vec![false; s2.len()], &mut |field, pairs, used| {
+                let left = s2.iter().zip(used).filter(|(_, u)| !**u).map(|(&r, _)| r).collect();
+                accept(field, pairs, left)
+            });
+
+            if found {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// depth first search giving each S1 player the earliest legal S2 player, which visits
+    /// S2 in the same order as the Dutch transpositions. `accept` is called with each
+    /// complete assignment and which of S2 were used
+    fn assign<F>(&mut self, s1: &[usize], s2: &[usize], tolerance: Strength, pairs: &mut Vec<(usize, usize)>, used: &mut [bool], accept: &mut F) -> bool
+    where
+        F: FnMut(&mut Self, &[(usize, usize)], &[bool]) -> bool,
+    {
+        if pairs.len() == s1.len() {
+            return accept(self, pairs, used);
+        }
+
+        if self.budget == 0 {
+            return false;
+        }
+        self.budget -= 1;
+
+        let top = s1[pairs.len()];
+        for idx in 0..s2.len() {
+            if used[idx] || !self.allowed(top, s2[idx], tolerance) {
+                continue;
+            }
+
+            used[idx] = true;
+            pairs.push((top, s2[idx]));
+            if self.assign(s1, s2, tolerance, pairs, used, accept) {
+                return true;
+            }
+            pairs.pop();
+            used[idx] = false;
+        }
+
+        false
+    }
+
+    /// records the brackets as they were paired, why each floater was left over and who
+    /// each S1 player passed over in S2 for having already played them
+    fn trace(&self, pairs: &[(usize, usize)], trace: &mut PairingTrace) {
+        let number = |rank: usize| self.entrants[rank].player.get_number();
+        let opponent = |rank: usize| pairs.iter().find_map(|&(a, b)| (a == rank).then_some(b).or((b == rank).then_some(a)));
+
+        for (bracket, moved_down, left) in &self.paired_brackets {
+            let resident = bracket[(*moved_down).min(bracket.len() - 1)];
+            trace.brackets.push(ScoreBracket {
+                points: self.entrants[resident].points,
+                players: bracket.iter().map(|&rank| number(rank)).collect(),
+            });
+
+            for &floater in left {
+                let Some(opponent) = opponent(floater) else {
+                    continue;
+                };
+                let (from, to) = (self.entrants[floater].points, self.entrants[opponent].points);
+                if to >= from || trace.floats.iter().any(|f| f.player == number(floater)) {
+                    continue;
+                }
+
+                let reason = if !bracket.iter().any(|&other| other != floater && self.allowed(floater, other, Strength::Strong)) {
+                    FloatReason::NoOpponentInBracket
+                } else if bracket.len() % 2 == 1 && left.len() == 1 {
+                    FloatReason::OddBracket
+                } else {
+                    FloatReason::PairingTheRest
+                };
+                trace.floats.push(Float {
+                    player: number(floater),
+                    opponent: number(opponent),
+                    from,
+                    to,
+                    reason,
+                });
+            }
+
+            // S1 players take the first S2 player they may play, in rank order
+            let mut bracket_pairs = pairs
+                .iter()
+                .map(|&(a, b)| (a.min(b), a.max(b)))
+                .filter(|&(a, b)| bracket.contains(&a) && bracket.contains(&b))
+                .collect::<Vec<(usize, usize)>>();
+            bracket_pairs.sort();
+            let s1 = bracket_pairs.iter().map(|&(a, _)| a).collect::<Vec<usize>>();
+            let mut taken = Vec::new();
+            for &(a, b) in &bracket_pairs {
+                for &other in bracket.iter().filter(|&&o| o > a && o < b && !s1.contains(&o) && !taken.contains(&o)) {
+                    let (pa, po) = (self.entrants[a].player, self.entrants[other].player);
+                    if pa.has_played(po.get_number()) && self.context.tags_allow(pa, po) {
+                        trace.rematches_avoided.push((pa.get_number(), po.get_number()));
+                    }
+                }
+                taken.push(b);
+            }
+        }
+    }
+
+    /// (white, black) following the colour allocation rules
+    fn colours(&self, higher: usize, lower: usize) -> (usize, usize) {
+        let (h, l) = (&self.entrants[higher], &self.entrants[lower]);
+        let (ph, pl) = (h.preference, l.preference);
+
+        let higher_colour = match (ph.colour, pl.colour) {
+            (None, None) => {
+                if h.player.get_number() % 2 == 1 {
+                    INITIAL_COLOUR
+                } else {
+                    !INITIAL_COLOUR
+                }
+            },
+            (Some(c), None) => c,
+            (None, Some(c)) => !c,
+            (Some(ch), Some(cl)) if ch != cl => ch,
+            (Some(ch), Some(_)) if ph.strength != pl.strength => {
+                if ph.strength > pl.strength { ch } else { !ch }
+            },
+            (Some(ch), Some(_)) => {
+                // both want the same colour as strongly, if one has the bigger imbalance they get it
+                let (dh, dl) = (h.player.colour_balance().abs(), l.player.colour_balance().abs());
+                if ph.strength == Strength::Absolute && dh != dl {
+                    if dh > dl { ch } else { !ch }
+                } else {
+                    // alternate from the latest round they had different colours
+                    h.player
+                        .get_colours()
+                        .iter()
+                        .rev()
+                        .zip(l.player.get_colours().iter().rev())
+                        .find(|(a, b)| a != b)
+                        .map_or(ch, |(&a, _)| !a)
+                }
+            },
+        };
+
+        match higher_colour {
+            Colour::White => (higher, lower),
+            Colour::Black => (lower, higher),
+        }
+    }
+}
+
+impl PairingEngine for DutchEngine {
+    fn name(&self) -> &'static str {
+        "FIDE Dutch"
+    }
+
+    fn picks_bye(&self) -> bool {
+        true
+    }
+
+    /// the dutch system is fully deterministic so `rng` is never used
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, _rng: &mut dyn RngCore, trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError> {
+        if players.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut order = (0..players.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| {
+            (Reverse(players[i].caluculate_match_points(context.scoring)), players[i].get_number())
+        });
+
+        // the bye goes to the lowest ranked player who has not had one
+        let mut byes: Vec<Option<usize>> = vec![None];
+        if players.len() % 2 == 1 {
+            byes = order
+                .iter()
+                .rev()
+                .filter(|&&i| !players[i].has_had_bye())
+                .map(|&i| Some(i))
+                .collect();
+        }
+
+        let mut rematch_options = vec![false];
+        if context.rematches == RematchPolicy::AllowIfUnavoidable {
+            rematch_options.push(true);
+        }
+
+        for rematches in rematch_options {
+            trace.byes_tried.clear();
+            for &bye in &byes {
+                let ranked = order.iter().copied().filter(|&i| Some(i) != bye).collect::<Vec<usize>>();
+                let mut field = Field {
+                    entrants: ranked
+                        .iter()
+                        .map(|&i| Entrant {
+                            player: &players[i],
+                            points: players[i].caluculate_match_points(context.scoring),
+                            preference: preference(&players[i]),
+                        })
+                        .collect(),
+                    rematches,
+                    context: *context,
+                    budget: 0,
+                    paired_brackets: Vec::new(),
+                };
+
+                let Some(pairs) = field.pair() else {
+                    trace.byes_tried.extend(bye.map(|b| players[b].get_number()));
+                    continue;
+                };
+                field.trace(&pairs, trace);
+
+                let mut tables = pairs
+                    .into_iter()
+                    .map(|(a, b)| {
+                        let (white, black) = field.colours(a.min(b), a.max(b));
+                        (ranked[white], Some(ranked[black]), a.min(b))
+                    })
+                    .collect::<Vec<(usize, Option<usize>, usize)>>();
+
+                // boards ordered by the rank of their higher player, bye last
+                tables.sort_by_key(|&(_, _, rank)| rank);
+                if let Some(bye) = bye {
+                    tables.push((bye, None, usize::MAX));
+                }
+
+                let mut slots = players.drain(..).map(Some).collect::<Vec<Option<Player>>>();
+                let pairings = tables
+                    .into_iter()
+                    .map(|(white, black, _)| {
+                        let white = slots[white].take().unwrap();
+                        let black = black.map(|b| slots[b].take().unwrap());
+                        Pairing::new(white, black)
+                    })
+                    .collect();
+
+                return Ok(pairings);
+            }
+        }
+
+        Err(PairingError::NoLegalPairing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::PairingSystem;
+    use crate::swiss::Outcome;
+    use crate::tournament::Tournament;
+
+    /// each line of an expected pairings file is `white black` or `bye player`
+    fn expected_pairings(path: &str) -> Vec<(u16, Option<u16>)> {
+        let mut expected = std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .map(|l| {
+                let mut parts = l.split_whitespace();
+                let white = parts.next().unwrap();
+                let black = parts.next().unwrap();
+                match white {
+                    "bye" => (black.parse().unwrap(), None),
+                    _ => (white.parse().unwrap(), Some(black.parse().unwrap())),
+                }
+            })
+            .collect::<Vec<(u16, Option<u16>)>>();
+        expected.sort();
+        expected
+    }
+
+    fn check_fixture(name: &str) {
+        let mut tournament = Tournament::read_from_file(format!("../test-files/dutch/{}.sts", name)).unwrap();
+        tournament.set_pairing_system(PairingSystem::Dutch);
+        tournament.set_auditing(true);
+        tournament.start_round().unwrap();
+
+        let mut found = tournament
+            .get_pairings()
+            .iter()
+            .map(|p| {
+                let (white, black) = p.get_players();
+                (white.get_number(), black.map(|b| b.get_number()))
+            })
+            .collect::<Vec<(u16, Option<u16>)>>();
+        found.sort();
+
+        assert_eq!(found, expected_pairings(&format!("../test-files/dutch/{}.pairings", name)));
+
+        // the engine traced a bracket for everyone it paired, floaters in each they moved into
+        let audit = &tournament.get_audits()[0];
+        for &(white, black) in found.iter().filter(|(_, black)| black.is_some()) {
+            for number in [white, black.unwrap()] {
+                assert!(audit.brackets.iter().any(|b| b.players.contains(&number)));
+            }
+        }
+        for float in &audit.floats {
+            assert!(audit.brackets.iter().filter(|b| b.players.contains(&float.player)).count() > 1);
+        }
+    }
+
+    /// the tables of a round as `(white, black, outcome)` and who had the bye
+    type TrfRound = (Vec<(u16, u16, Outcome)>, Option<u16>);
+
+    /// the number of players and every round of a FIDE tournament report (TRF-16) file, the
+    /// bye being the pairing allocated one. Players are ranked by their starting number and
+    /// lines starting `###` are comments
+    fn read_trf(path: &str) -> (u16, Vec<TrfRound>) {
+        let text = std::fs::read_to_string(path).unwrap();
+        let mut players = 0;
+        let mut rounds = Vec::new();
+        for line in text.lines().filter(|l| l.starts_with("001")) {
+            players += 1;
+            let number = line[4..8].trim().parse::<u16>().unwrap();
+            // each round takes ten columns from column 92, the opponent, colour and result
+            for (round, start) in (91..line.len()).step_by(10).enumerate() {
+                let game = &line[start..(start + 9).min(line.len())];
+                let opponent = game[..4].trim().parse::<u16>().unwrap();
+                let (colour, result) = (&game[5..6], &game[7..8]);
+                if rounds.len() <= round {
+                    rounds.resize(round + 1, (Vec::new(), None));
+                }
+                match (opponent, colour, result) {
+                    (0, _, "U") => rounds[round].1 = Some(number),
+                    (0, _, _) => panic!("requested byes are not supported, found {} in {}", result, line),
+                    (_, "w", _) => {
+                        let outcome = match result {
+                            "1" => Outcome::Win,
+                            "0" => Outcome::Loss,
+                            "=" => Outcome::Tie,
+                            "+" => Outcome::ForfeitWin,
+                            "-" => Outcome::ForfeitLoss,
+                            _ => panic!("unknown result {} in {}", result, line),
+                        };
+                        rounds[round].0.push((number, opponent, outcome));
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        (players, rounds)
+    }
+
+    /// plays a whole event from a TRF-16 file, every round must pair exactly as it lists
+    fn check_trf(name: &str) {
+        let (players, rounds) = read_trf(&format!("../test-files/dutch/{}.trf", name));
+        let mut tournament = Tournament::new(name.to_string(), (1..=players).map(|n| Player::new(n.to_string(), n)).collect());
+        tournament.set_pairing_system(PairingSystem::Dutch);
+        for (round, (mut expected, bye)) in rounds.into_iter().enumerate() {
+            tournament.start_round().unwrap();
+            let mut found = tournament
+                .get_pairings()
+                .iter()
+                .filter_map(|p| match p.get_players() {
+                    (white, Some(black)) => Some((white.get_number(), black.get_number())),
+                    _ => None,
+                })
+                .collect::<Vec<(u16, u16)>>();
+            found.sort();
+            expected.sort_by_key(|&(white, black, _)| (white, black));
+            let tables = expected.iter().map(|&(white, black, _)| (white, black)).collect::<Vec<(u16, u16)>>();
+            assert_eq!(found, tables, "{} round {}", name, round + 1);
+            let found_bye = tournament.get_pairings().iter().find(|p| p.is_bye()).map(|p| p.get_players().0.get_number());
+            assert_eq!(found_bye, bye, "{} round {} bye", name, round + 1);
+
+            for (white, _, outcome) in expected {
+                let idx = tournament.get_pairings().iter().position(|p| p.get_players().0.get_number() == white).unwrap();
+                tournament.report_match(idx, outcome).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+    }
+
+    /// every tournament file in the fixtures, see test-files/dutch/README.md for where they come from
+    #[test]
+    fn tournaments_pair_every_round() {
+        let mut names = std::fs::read_dir("../test-files/dutch")
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|e| e == "trf"))
+            .map(|p| p.file_stem().unwrap().to_string_lossy().into_owned())
+            .collect::<Vec<String>>();
+        names.sort();
+        assert!(!names.is_empty());
+        for name in names {
+            check_trf(&name);
+        }
+    }
+
+    #[test]
+    fn round_one_even() {
+        check_fixture("six_round_1");
+    }
+
+    #[test]
+    fn round_two_floaters() {
+        check_fixture("six_round_2");
+    }
+
+    #[test]
+    fn round_one_bye() {
+        check_fixture("five_round_1");
+    }
+
+    #[test]
+    fn round_two_bye() {
+        check_fixture("five_round_2");
+    }
+
+    #[test]
+    fn preferences() {
+        let mut player = Player::new("1".to_string(), 1);
+        assert_eq!(preference(&player).strength, Strength::None);
+
+        player.add_colour(Colour::White);
+        assert_eq!(preference(&player), Preference { colour: Some(Colour::Black), strength: Strength::Strong });
+
+        player.add_colour(Colour::Black);
+        assert_eq!(preference(&player), Preference { colour: Some(Colour::White), strength: Strength::Mild });
+
+        player.add_colour(Colour::Black);
+        assert_eq!(preference(&player), Preference { colour: Some(Colour::White), strength: Strength::Absolute });
+    }
+
+    #[test]
+    fn never_pairs_absolute_conflict() {
+        // both have had black twice so can not meet
+        let mut players = vec![Player::new("1".to_string(), 1), Player::new("2".to_string(), 2)];
+        for player in &mut players {
+            player.add_opponent(9, Outcome::Tie);
+            player.add_opponent(8, Outcome::Tie);
+            player.add_colour(Colour::Black);
+            player.add_colour(Colour::Black);
+        }
+
+        let context = PairingContext {
+            scoring: crate::DEFUALT_SCORING,
+            rematches: RematchPolicy::Never,
+            separate_tags: false,
+            tag_exceptions: &[],
+            round: 2,
+            seating: None,
+        };
+        assert_eq!(DutchEngine.pair(&mut players, &context, &mut rand::rng(), &mut PairingTrace::default()), Err(PairingError::NoLegalPairing));
+        assert_eq!(players.len(), 2);
+    }
+
+    #[test]
+    fn many_rounds_no_rematches() {
+        let mut tournament = Tournament::new("Dutch".to_string(), (1..=12).map(|n| Player::new(n.to_string(), n)).collect());
+        tournament.set_pairing_system(PairingSystem::Dutch);
+
+        for round in 0..5 {
+            tournament.start_round().unwrap();
+            for idx in 0..tournament.get_pairings().len() {
+                let outcome = match (idx + round) % 3 {
+                    0 => Outcome::Win,
+                    1 => Outcome::Loss,
+                    _ => Outcome::Tie,
+                };
+                tournament.report_match(idx, outcome).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        for player in tournament.get_players() {
+            let mut opponents = player.get_matches().iter().map(|&(opp, _)| opp).collect::<Vec<u16>>();
+            opponents.sort();
+            opponents.dedup();
+            assert_eq!(opponents.len(), 5);
+            assert!(player.colour_balance().abs() <= 2);
+        }
+    }
+}
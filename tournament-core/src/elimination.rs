@@ -0,0 +1,677 @@
+//! Knockout brackets played after, or instead of, the swiss rounds. Brackets only know player numbers,
+//! the [`Tournament`](crate::tournament::Tournament) hands the players out for each match.
+
+use std::error::Error;
+use std::fmt::Display;
+
+/// A single match of a bracket, `top` is always the better seed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BracketMatch {
+    pub top: u16,
+    pub bottom: u16,
+    pub winner: Option<u16>,
+}
+
+impl BracketMatch {
+    pub const fn new(top: u16, bottom: u16) -> Self {
+        Self {
+            top,
+            bottom,
+            winner: None,
+        }
+    }
+
+    pub fn loser(&self) -> Option<u16> {
+        self.winner.map(|w| if w == self.top { self.bottom } else { self.top })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BracketError {
+    /// every bracket needs two players and single elimination needs a power of two
+    InvalidSize(usize),
+    /// the player is not in an undecided match of the current round
+    NotInRound(u16),
+    Finished,
+    /// a loaded round, counting from 0, is not the one the bracket would have paired
+    InvalidRound(usize),
+}
+
+impl Display for BracketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSize(size) => write!(f, "A bracket can not be made from {} players", size),
+            Self::NotInRound(number) => write!(f, "Player {} is not in an undecided match this round", number),
+            Self::Finished => write!(f, "The bracket has already finished"),
+            Self::InvalidRound(round) => write!(f, "Round {} does not follow from the rounds before it", round + 1),
+        }
+    }
+}
+
+impl Error for BracketError {}
+
+/// seed numbers, starting at 1, in bracket order so that neighbouring pairs meet
+/// first and the top two seeds can only meet in the final. 8 gives 1 8 4 5 2 7 3 6
+pub fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let round_size = order.len() * 2 + 1;
+        order = order.into_iter().flat_map(|seed| [seed, round_size - seed]).collect();
+    }
+
+    order
+}
+
+/// A seeded knockout bracket, the winner of every match moves on and the loser is out
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SingleElimination {
+    /// player numbers best seed first
+    seeds: Vec<u16>,
+    rounds: Vec<Vec<BracketMatch>>,
+}
+
+impl SingleElimination {
+    pub fn new(seeds: Vec<u16>) -> Result<Self, BracketError> {
+        if seeds.len() < 2 || !seeds.len().is_power_of_two() {
+            return Err(BracketError::InvalidSize(seeds.len()));
+        }
+
+        Ok(Self {
+            seeds,
+            rounds: Vec::new(),
+        })
+    }
+
+    /// used when loading a bracket that has already been played, the rounds are played out
+    /// again so only rounds that follow from the seeds and the results before them are taken
+    pub fn from_rounds(seeds: Vec<u16>, rounds: Vec<Vec<BracketMatch>>) -> Result<Self, BracketError> {
+        let mut bracket = Self::new(seeds)?;
+        for (idx, round) in rounds.into_iter().enumerate() {
+            let expected = bracket.next_round()?;
+            let same_matches = expected.len() == round.len()
+                && expected.iter().zip(&round).all(|(a, b)| (a.top, a.bottom) == (b.top, b.bottom));
+            if !same_matches {
+                return Err(BracketError::InvalidRound(idx));
+            }
+
+            for winner in round.into_iter().filter_map(|m| m.winner) {
+                bracket.report_winner(winner)?;
+            }
+        }
+
+        Ok(bracket)
+    }
+
+    pub fn get_seeds(&self) -> &[u16] {
+        &self.seeds
+    }
+
+    pub fn get_rounds(&self) -> &[Vec<BracketMatch>] {
+        &self.rounds
+    }
+
+    fn round_decided(round: &[BracketMatch]) -> bool {
+        round.iter().all(|m| m.winner.is_some())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.champion().is_some()
+    }
+
+    pub fn champion(&self) -> Option<u16> {
+        match self.rounds.last()?.as_slice() {
+            [final_match] => final_match.winner,
+            _ => None,
+        }
+    }
+
+    /// the matches to play next. A round that was started but not decided, such as
+    /// after loading a save, is handed out again rather than starting a new one
+    pub fn next_round(&mut self) -> Result<&[BracketMatch], BracketError> {
+        if self.is_finished() {
+            return Err(BracketError::Finished);
+        }
+
+        let needs_round = self.rounds.last().is_none_or(|round| Self::round_decided(round));
+        if needs_round {
+            let round = match self.rounds.last() {
+                None => bracket_order(self.seeds.len())
+                    .chunks(2)
+                    .map(|pair| BracketMatch::new(self.seeds[pair[0] - 1], self.seeds[pair[1] - 1]))
+                    .collect(),
+                Some(previous) => previous
+                    .chunks(2)
+                    .map(|pair| {
+                        let (a, b) = (pair[0].winner.unwrap(), pair[1].winner.unwrap());
+                        if self.seed_of(a) < self.seed_of(b) {
+                            BracketMatch::new(a, b)
+                        } else {
+                            BracketMatch::new(b, a)
+                        }
+                    })
+                    .collect(),
+            };
+            self.rounds.push(round);
+        }
+
+        Ok(self.rounds.last().unwrap())
+    }
+
+    fn seed_of(&self, number: u16) -> usize {
+        self.seeds.iter().position(|&s| s == number).unwrap_or(usize::MAX)
+    }
+
+    /// marks `winner` as having won their match in the current round
+    pub fn report_winner(&mut self, winner: u16) -> Result<(), BracketError> {
+        let bracket_match = self
+            .rounds
+            .last_mut()
+            .into_iter()
+            .flatten()
+            .find(|m| m.winner.is_none() && (m.top == winner || m.bottom == winner))
+            .ok_or(BracketError::NotInRound(winner))?;
+        bracket_match.winner = Some(winner);
+
+        Ok(())
+    }
+
+    /// players still in the bracket
+    pub fn remaining(&self) -> Vec<u16> {
+        match self.rounds.last() {
+            None => self.seeds.clone(),
+            Some(round) if Self::round_decided(round) => round.iter().filter_map(|m| m.winner).collect(),
+            Some(round) => round.iter().flat_map(|m| [m.top, m.bottom]).collect(),
+        }
+    }
+}
+
+/// One match of a double elimination bracket, a missing player is a bye
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Slot {
+    top: Option<u16>,
+    bottom: Option<u16>,
+    winner: Option<u16>,
+}
+
+impl Slot {
+    /// the better seed is put on top, byes always go to the bottom
+    fn new(a: Option<u16>, b: Option<u16>, seeds: &[u16]) -> Self {
+        let seed_of = |n: Option<u16>| n.and_then(|n| seeds.iter().position(|&s| s == n)).unwrap_or(usize::MAX);
+        let (top, bottom) = if seed_of(a) <= seed_of(b) { (a, b) } else { (b, a) };
+        Self {
+            top,
+            bottom,
+            winner: None,
+        }
+    }
+
+    fn is_real(&self) -> bool {
+        self.top.is_some() && self.bottom.is_some()
+    }
+
+    fn is_decided(&self) -> bool {
+        !self.is_real() || self.winner.is_some()
+    }
+
+    /// who moves on, a match against a bye is won without being played
+    fn advancing(&self) -> Option<u16> {
+        if self.is_real() { self.winner } else { self.top.or(self.bottom) }
+    }
+
+    fn losing(&self) -> Option<u16> {
+        let winner = self.winner?;
+        if self.top == Some(winner) { self.bottom } else { self.top }
+    }
+}
+
+/// A bracket where players are only out after their second loss. Losers of the
+/// winners bracket drop into the losers bracket and the two champions meet in a
+/// grand final, optionally played twice if the losers bracket champion wins the first.
+/// Fields that are not a power of two give the top seeds byes in the first round
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DoubleElimination {
+    /// player numbers best seed first
+    seeds: Vec<u16>,
+    bracket_reset: bool,
+    /// winners of every played match in the order they were reported
+    results: Vec<u16>,
+    winners: Vec<Vec<Slot>>,
+    losers: Vec<Vec<Slot>>,
+    grand_final: Vec<Slot>,
+}
+
+impl DoubleElimination {
+    pub fn new(seeds: Vec<u16>, bracket_reset: bool) -> Result<Self, BracketError> {
+        if seeds.len() < 2 {
+            return Err(BracketError::InvalidSize(seeds.len()));
+        }
+
+        let size = seeds.len().next_power_of_two();
+        let first = bracket_order(size)
+            .chunks(2)
+            .map(|pair| {
+                let player = |seed: usize| seeds.get(seed - 1).copied();
+                Slot::new(player(pair[0]), player(pair[1]), &seeds)
+            })
+            .collect();
+
+        let mut bracket = Self {
+            seeds,
+            bracket_reset,
+            results: Vec::new(),
+            winners: vec![first],
+            losers: Vec::new(),
+            grand_final: Vec::new(),
+        };
+        bracket.advance();
+
+        Ok(bracket)
+    }
+
+    /// rebuilds a bracket by replaying its results
+    pub fn from_results(seeds: Vec<u16>, bracket_reset: bool, results: &[u16]) -> Result<Self, BracketError> {
+        let mut bracket = Self::new(seeds, bracket_reset)?;
+        for &winner in results {
+            bracket.report_winner(winner)?;
+        }
+
+        Ok(bracket)
+    }
+
+    pub fn get_seeds(&self) -> &[u16] {
+        &self.seeds
+    }
+
+    pub fn has_bracket_reset(&self) -> bool {
+        self.bracket_reset
+    }
+
+    pub fn get_results(&self) -> &[u16] {
+        &self.results
+    }
+
+    fn round_winners(round: &[Slot]) -> Vec<Option<u16>> {
+        round.iter().map(|s| s.advancing()).collect()
+    }
+
+    fn round_decided(round: &[Slot]) -> bool {
+        round.iter().all(|s| s.is_decided())
+    }
+
+    /// winners bracket losers dropping into the losers bracket are reordered so they
+    /// do not meet someone from their own part of the bracket straight away
+    fn drop_order(losers: Vec<Option<u16>>, drop: usize) -> Vec<Option<u16>> {
+        let mut losers = losers;
+        if drop.is_multiple_of(2) {
+            losers.reverse();
+        } else {
+            let half = losers.len() / 2;
+            losers.rotate_left(half);
+        }
+
+        losers
+    }
+
+    fn pair_up(&self, players: &[Option<u16>]) -> Vec<Slot> {
+        players.chunks(2).map(|pair| Slot::new(pair[0], pair[1], &self.seeds)).collect()
+    }
+
+    /// the champion of the losers bracket, with only two players it is the loser of the final
+    fn losers_champion(&self) -> Option<Option<u16>> {
+        if self.winners_rounds() == 1 {
+            let final_match = &self.winners[0][0];
+            return final_match.is_decided().then(|| final_match.losing());
+        }
+
+        let rounds = 2 * (self.winners_rounds() - 1);
+        let last = self.losers.get(rounds - 1)?;
+        Self::round_decided(last).then(|| last[0].advancing())
+    }
+
+    fn winners_rounds(&self) -> usize {
+        self.seeds.len().next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// creates every round whose players are known
+    fn advance(&mut self) {
+        loop {
+            let mut changed = false;
+
+            // winners bracket
+            let last = self.winners.last().unwrap();
+            if last.len() > 1 && Self::round_decided(last) {
+                let round = self.pair_up(&Self::round_winners(last));
+                self.winners.push(round);
+                changed = true;
+            }
+
+            // losers bracket, even rounds pair the survivors and odd rounds take the drops
+            let lb_rounds = 2 * (self.winners_rounds() - 1);
+            let next = self.losers.len();
+            if next < lb_rounds {
+                let round = if next == 0 {
+                    Self::round_decided(&self.winners[0]).then(|| {
+                        let losers = self.winners[0].iter().map(|s| s.losing()).collect::<Vec<Option<u16>>>();
+                        self.pair_up(&losers)
+                    })
+                } else if next.is_multiple_of(2) {
+                    let previous = &self.losers[next - 1];
+                    Self::round_decided(previous).then(|| self.pair_up(&Self::round_winners(previous)))
+                } else {
+                    let previous = &self.losers[next - 1];
+                    let drop = next / 2;
+                    self.winners
+                        .get(drop + 1)
+                        .filter(|wb| Self::round_decided(wb) && Self::round_decided(previous))
+                        .map(|wb| {
+                            let losers = wb.iter().map(|s| s.losing()).collect();
+                            let dropped = Self::drop_order(losers, drop);
+                            Self::round_winners(previous)
+                                .into_iter()
+                                .zip(dropped)
+                                .map(|(survivor, dropped)| Slot::new(survivor, dropped, &self.seeds))
+                                .collect()
+                        })
+                };
+
+                if let Some(round) = round {
+                    self.losers.push(round);
+                    changed = true;
+                }
+            }
+
+            // grand final and its reset
+            let winners_final = self.winners.last().filter(|r| r.len() == 1 && Self::round_decided(r));
+            match (self.grand_final.as_slice(), winners_final, self.losers_champion()) {
+                ([], Some(wb), Some(lb)) => {
+                    self.grand_final.push(Slot {
+                        top: wb[0].advancing(),
+                        bottom: lb,
+                        winner: None,
+                    });
+                    changed = true;
+                },
+                ([first], _, _) if self.bracket_reset && first.is_real() && first.winner.is_some() && first.winner == first.bottom => {
+                    self.grand_final.push(Slot {
+                        winner: None,
+                        ..*first
+                    });
+                    changed = true;
+                },
+                _ => {},
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    pub fn champion(&self) -> Option<u16> {
+        let last = self.grand_final.last()?;
+        if !last.is_decided() {
+            return None;
+        }
+
+        let reset_needed = self.bracket_reset && self.grand_final.len() == 1 && last.is_real() && last.winner == last.bottom;
+        if reset_needed {
+            return None;
+        }
+
+        last.advancing()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.champion().is_some()
+    }
+
+    fn slots_mut(&mut self) -> impl Iterator<Item = &mut Slot> {
+        self.winners
+            .iter_mut()
+            .chain(self.losers.iter_mut())
+            .flatten()
+            .chain(self.grand_final.iter_mut())
+    }
+
+    /// every match that can be played right now
+    pub fn next_round(&self) -> Result<Vec<BracketMatch>, BracketError> {
+        if self.is_finished() {
+            return Err(BracketError::Finished);
+        }
+
+        let matches = self
+            .winners
+            .iter()
+            .chain(self.losers.iter())
+            .flatten()
+            .chain(self.grand_final.iter())
+            .filter(|s| !s.is_decided())
+            .map(|s| BracketMatch::new(s.top.unwrap(), s.bottom.unwrap()))
+            .collect();
+
+        Ok(matches)
+    }
+
+    pub fn report_winner(&mut self, winner: u16) -> Result<(), BracketError> {
+        let slot = self
+            .slots_mut()
+            .find(|s| !s.is_decided() && (s.top == Some(winner) || s.bottom == Some(winner)))
+            .ok_or(BracketError::NotInRound(winner))?;
+        slot.winner = Some(winner);
+        self.results.push(winner);
+        self.advance();
+
+        Ok(())
+    }
+
+    /// players with fewer than two losses
+    pub fn remaining(&self) -> Vec<u16> {
+        let mut losses = self.seeds.iter().map(|&s| (s, 0)).collect::<Vec<(u16, u8)>>();
+        let played = self
+            .winners
+            .iter()
+            .chain(self.losers.iter())
+            .flatten()
+            .chain(self.grand_final.iter());
+        for loser in played.filter_map(|s| s.losing()) {
+            if let Some(entry) = losses.iter_mut().find(|(s, _)| *s == loser) {
+                entry.1 += 1;
+            }
+        }
+
+        losses.into_iter().filter(|&(_, l)| l < 2).map(|(s, _)| s).collect()
+    }
+}
+
+/// Every knockout format a tournament can finish with
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bracket {
+    Single(SingleElimination),
+    Double(DoubleElimination),
+}
+
+impl Bracket {
+    pub fn next_round(&mut self) -> Result<Vec<BracketMatch>, BracketError> {
+        match self {
+            Self::Single(bracket) => bracket.next_round().map(|round| round.to_vec()),
+            Self::Double(bracket) => bracket.next_round(),
+        }
+    }
+
+    pub fn report_winner(&mut self, winner: u16) -> Result<(), BracketError> {
+        match self {
+            Self::Single(bracket) => bracket.report_winner(winner),
+            Self::Double(bracket) => bracket.report_winner(winner),
+        }
+    }
+
+    pub fn champion(&self) -> Option<u16> {
+        match self {
+            Self::Single(bracket) => bracket.champion(),
+            Self::Double(bracket) => bracket.champion(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.champion().is_some()
+    }
+
+    pub fn remaining(&self) -> Vec<u16> {
+        match self {
+            Self::Single(bracket) => bracket.remaining(),
+            Self::Double(bracket) => bracket.remaining(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn seeded_order() {
+        assert_eq!(bracket_order(2), vec![1, 2]);
+        assert_eq!(bracket_order(4), vec![1, 4, 2, 3]);
+        assert_eq!(bracket_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn rejects_bad_sizes() {
+        assert_eq!(SingleElimination::new(vec![1]), Err(BracketError::InvalidSize(1)));
+        assert_eq!(SingleElimination::new(vec![1, 2, 3]), Err(BracketError::InvalidSize(3)));
+    }
+
+    #[test]
+    fn top_seeds_meet_in_final() {
+        // seeds 1 to 8 are players 11 to 18
+        let mut bracket = SingleElimination::new((11..19).collect()).unwrap();
+        let first = bracket.next_round().unwrap().to_vec();
+        assert_eq!(first[0], BracketMatch::new(11, 18));
+        assert_eq!(first[1], BracketMatch::new(14, 15));
+
+        while !bracket.is_finished() {
+            let round = bracket.next_round().unwrap().to_vec();
+            for m in round {
+                // lower seed always wins
+                bracket.report_winner(m.top).unwrap();
+            }
+        }
+
+        let rounds = bracket.get_rounds();
+        assert_eq!(rounds.len(), 3);
+        assert_eq!(rounds[1], vec![BracketMatch { winner: Some(11), ..BracketMatch::new(11, 14) }, BracketMatch { winner: Some(12), ..BracketMatch::new(12, 13) }]);
+        assert_eq!(bracket.champion(), Some(11));
+        assert_eq!(bracket.next_round(), Err(BracketError::Finished));
+    }
+
+    #[test]
+    fn unfinished_round_is_resumed() {
+        let mut bracket = SingleElimination::new(vec![1, 2, 3, 4]).unwrap();
+        bracket.next_round().unwrap();
+        bracket.report_winner(4).unwrap();
+        assert_eq!(bracket.report_winner(1), Err(BracketError::NotInRound(1)));
+
+        let round = bracket.next_round().unwrap();
+        assert_eq!(round.len(), 2);
+        assert_eq!(bracket.get_rounds().len(), 1);
+        assert_eq!(bracket.remaining(), vec![1, 4, 2, 3]);
+    }
+
+    /// plays the bracket to the end with random winners and returns every match played
+    fn play_out(bracket: &mut DoubleElimination, rng: &mut StdRng) -> Vec<BracketMatch> {
+        let mut played = Vec::new();
+        while !bracket.is_finished() {
+            let round = bracket.next_round().unwrap();
+            assert!(!round.is_empty());
+            for mut m in round {
+                let winner = if rng.random_bool(0.5) { m.top } else { m.bottom };
+                bracket.report_winner(winner).unwrap();
+                m.winner = Some(winner);
+                played.push(m);
+            }
+        }
+
+        played
+    }
+
+    #[test]
+    fn double_elimination_needs_two_losses() {
+        let mut rng = StdRng::seed_from_u64(0xde);
+        for size in 2..=17 {
+            for bracket_reset in [false, true] {
+                let mut bracket = DoubleElimination::new((1..=size).collect(), bracket_reset).unwrap();
+                let played = play_out(&mut bracket, &mut rng);
+                let champion = bracket.champion().unwrap();
+
+                for player in 1..=size {
+                    let losses = played.iter().filter(|m| m.loser() == Some(player)).count();
+                    let last_loss = played.iter().rposition(|m| m.loser() == Some(player));
+                    let last_match = played.iter().rposition(|m| m.top == player || m.bottom == player);
+                    if losses == 2 {
+                        assert_eq!(last_loss, last_match);
+                    }
+
+                    if player == champion {
+                        assert!(losses <= 1);
+                    } else if bracket_reset {
+                        assert_eq!(losses, 2);
+                    } else {
+                        assert!(losses == 2 || (losses == 1 && played.last().unwrap().loser() == Some(player)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn drops_avoid_rematches() {
+        let mut rng = StdRng::seed_from_u64(0xd0);
+        for _ in 0..200 {
+            let mut bracket = DoubleElimination::new((1..=8).collect(), false).unwrap();
+            let played = play_out(&mut bracket, &mut rng);
+
+            // the first round the winners bracket losers drop into
+            for slot in &bracket.losers[1] {
+                let (a, b) = (slot.top.unwrap(), slot.bottom.unwrap());
+                let meetings = played
+                    .iter()
+                    .filter(|m| (m.top == a && m.bottom == b) || (m.top == b && m.bottom == a))
+                    .count();
+                assert_eq!(meetings, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn top_seeds_get_byes() {
+        let bracket = DoubleElimination::new(vec![1, 2, 3, 4, 5], true).unwrap();
+        assert_eq!(bracket.next_round().unwrap(), vec![BracketMatch::new(4, 5)]);
+    }
+
+    #[test]
+    fn grand_final_reset() {
+        let mut bracket = DoubleElimination::new(vec![1, 2], true).unwrap();
+        bracket.report_winner(1).unwrap();
+        assert_eq!(bracket.next_round().unwrap(), vec![BracketMatch::new(1, 2)]);
+        bracket.report_winner(2).unwrap();
+        assert_eq!(bracket.champion(), None);
+        bracket.report_winner(2).unwrap();
+        assert_eq!(bracket.champion(), Some(2));
+
+        let replayed = DoubleElimination::from_results(vec![1, 2], true, bracket.get_results()).unwrap();
+        assert_eq!(replayed, bracket);
+
+        let mut no_reset = DoubleElimination::new(vec![1, 2], false).unwrap();
+        no_reset.report_winner(1).unwrap();
+        no_reset.report_winner(2).unwrap();
+        assert_eq!(no_reset.champion(), Some(2));
+    }
+}
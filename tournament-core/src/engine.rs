@@ -0,0 +1,450 @@
+use rand::RngCore;
+use rand::seq::SliceRandom;
+
+use crate::audit::{Float, FloatReason, PairingTrace, ScoreBracket};
+use crate::dutch::DutchEngine;
+use crate::matching::max_weight_matching;
+use crate::round_robin::{PodsEngine, RoundRobinEngine, Seating};
+use crate::player::Player;
+use crate::swiss::{generate_pairings_with, Pairing, PairingError, Points, RematchPolicy, ScoreConfig};
+
+/// Everything an engine needs to know about the tournament to pair a round
+#[derive(Clone, Copy, Debug)]
+pub struct PairingContext<'a> {
+    pub scoring: ScoreConfig,
+    pub rematches: RematchPolicy,
+    /// players sharing a tag may not be paired together
+    pub separate_tags: bool,
+    /// pairs sharing a tag who may be paired anyway, see [`fewest_tag_clashes`]
+    pub tag_exceptions: &'a [(u16, u16)],
+    /// the round being paired, counting from 0
+    pub round: u16,
+    /// the seats given out by [`PairingEngine::seat`] when the engine was first used
+    pub seating: Option<&'a Seating>,
+}
+
+impl PairingContext<'_> {
+    /// false for players kept apart because they share a tag
+    pub fn tags_allow(&self, p1: &Player, p2: &Player) -> bool {
+        let (n1, n2) = (p1.get_number(), p2.get_number());
+        !self.separate_tags
+            || !p1.shares_tag(p2)
+            || self.tag_exceptions.iter().any(|&pair| pair == (n1, n2) || pair == (n2, n1))
+    }
+}
+
+/// Turns the players of a round into pairings, on success `players` is emptied and on
+/// error every player must be handed back. Any randomness must come from `rng` so a
+/// round can be reproduced from the same seed. How the pairings were found is recorded
+/// in `trace`, what it holds after an error is thrown away
+pub trait PairingEngine {
+    fn name(&self) -> &'static str;
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, rng: &mut dyn RngCore, trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError>;
+
+    /// engines with their own rules for the bye are handed an odd number of players,
+    /// every other engine has the bye taken out by the tournament first
+    fn picks_bye(&self) -> bool {
+        false
+    }
+
+    /// engines playing to a fixed schedule seat the players once, the tournament keeps
+    /// the seating and hands it back in the context of every round after
+    fn seat(&self, _players: &[Player], _round: u16) -> Option<Seating> {
+        None
+    }
+}
+
+/// bracket by bracket pairing, see [`generate_pairings`]
+pub struct GreedyEngine;
+
+/// pairs the whole field at once by finding the maximum weight perfect matching
+/// of a graph where every edge is penalised for score differences, rematches,
+/// repeated byes and colour imbalance
+pub struct MatchingEngine;
+
+impl PairingEngine for GreedyEngine {
+    fn name(&self) -> &'static str {
+        "Greedy"
+    }
+
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, rng: &mut dyn RngCore, trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError> {
+        generate_pairings_with(players, context.scoring, context.rematches, |p1, p2| context.tags_allow(p1, p2), rng, trace)
+    }
+}
+
+/// every edge starts at this so penalties never make a weight negative
+const BASE_WEIGHT: i64 = 1 << 40;
+/// multiplied by the square of the score difference in half points
+const SCORE_PENALTY: i64 = 100;
+const REMATCH_PENALTY: i64 = 1 << 32;
+const REPEAT_BYE_PENALTY: i64 = 1 << 30;
+/// per game of imbalance when both players are due the same colour
+const COLOUR_PENALTY: i64 = 10;
+/// for a pair sharing a tag when working out which tags have to give way
+const TAG_PENALTY: i64 = 1 << 31;
+
+/// the pairs sharing a tag in a pairing of `players` with as few of them as possible, so
+/// only those tags have to give way when the round can not be paired otherwise. Rematches
+/// are avoided first and anyone may take the bye of an odd field
+pub fn fewest_tag_clashes(players: &[Player], context: &PairingContext) -> Vec<(u16, u16)> {
+    let count = players.len();
+    let mut edges = Vec::new();
+    for i in 0..count {
+        for j in i + 1..count {
+            let rematch = players[i].has_played(players[j].get_number());
+            if rematch && context.rematches == RematchPolicy::Never {
+                continue;
+            }
+
+            let mut weight = BASE_WEIGHT;
+            if rematch {
+                weight -= REMATCH_PENALTY;
+            }
+            if players[i].shares_tag(&players[j]) {
+                weight -= TAG_PENALTY;
+            }
+            edges.push((i, j, weight));
+        }
+
+        if count % 2 == 1 {
+            edges.push((i, count, BASE_WEIGHT));
+        }
+    }
+
+    let mate = max_weight_matching(&edges, true);
+    (0..count)
+        .filter_map(|i| match mate.get(i).copied().flatten() {
+            Some(j) if i < j && j < count && players[i].shares_tag(&players[j]) => Some((players[i].get_number(), players[j].get_number())),
+            _ => None,
+        })
+        .collect()
+}
+
+impl MatchingEngine {
+    fn pair_weight(p1: &Player, p2: &Player, scoring: ScoreConfig) -> i64 {
+        let difference = p1.caluculate_match_points(scoring).halves() as i64 - p2.caluculate_match_points(scoring).halves() as i64;
+        let mut penalty = difference * difference * SCORE_PENALTY;
+
+        if p1.has_played(p2.get_number()) {
+            penalty += REMATCH_PENALTY;
+        }
+
+        let (b1, b2) = (p1.colour_balance() as i64, p2.colour_balance() as i64);
+        if b1.signum() == b2.signum() {
+            penalty += i64::min(b1.abs(), b2.abs()) * COLOUR_PENALTY;
+        }
+
+        BASE_WEIGHT - penalty
+    }
+
+    /// the bye is treated as an opponent on zero points
+    fn bye_weight(player: &Player, scoring: ScoreConfig) -> i64 {
+        let points = player.caluculate_match_points(scoring).halves() as i64;
+        let mut penalty = points * points * SCORE_PENALTY;
+
+        if player.has_had_bye() {
+            penalty += REPEAT_BYE_PENALTY;
+        }
+
+        BASE_WEIGHT - penalty
+    }
+
+    /// the whole field is paired at once so every bracket is all the players on its points,
+    /// every float comes from the matching and every rematch edge was left out or penalised
+    fn trace(players: &[Player], points: &[Points], tables: &[(usize, Option<usize>)], context: &PairingContext, trace: &mut PairingTrace) {
+        let mut order = (0..players.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| (std::cmp::Reverse(points[i]), players[i].get_number()));
+        for &i in &order {
+            match trace.brackets.last_mut() {
+                Some(bracket) if bracket.points == points[i] => bracket.players.push(players[i].get_number()),
+                _ => trace.brackets.push(ScoreBracket {
+                    points: points[i],
+                    players: vec![players[i].get_number()],
+                }),
+            }
+        }
+
+        for &(p1, p2) in tables {
+            let Some(p2) = p2 else {
+                continue;
+            };
+            let (high, low) = if points[p1] >= points[p2] { (p1, p2) } else { (p2, p1) };
+            if points[high] > points[low] {
+                trace.floats.push(Float {
+                    player: players[high].get_number(),
+                    opponent: players[low].get_number(),
+                    from: points[high],
+                    to: points[low],
+                    reason: FloatReason::BestMatching,
+                });
+            }
+        }
+
+        if context.rematches == RematchPolicy::Never {
+            for (a, &i) in order.iter().enumerate() {
+                for &j in &order[a + 1..] {
+                    if points[i] == points[j] && players[i].has_played(players[j].get_number()) {
+                        trace.rematches_avoided.push((players[i].get_number(), players[j].get_number()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl PairingEngine for MatchingEngine {
+    fn name(&self) -> &'static str {
+        "Maximum Weight Matching"
+    }
+
+    /// the bye is one more vertex of the matching so it is weighed against every pairing
+    fn picks_bye(&self) -> bool {
+        true
+    }
+
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, rng: &mut dyn RngCore, trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError> {
+        if players.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // equal weights are broken by position so shuffle to keep brackets random
+        players.shuffle(rng);
+
+        let count = players.len();
+        let bye = count; // only used with an odd number of players
+        let mut edges = Vec::new();
+        for i in 0..count {
+            for j in i + 1..count {
+                let rematch = players[i].has_played(players[j].get_number());
+                if (rematch && context.rematches == RematchPolicy::Never) || !context.tags_allow(&players[i], &players[j]) {
+                    continue;
+                }
+
+                edges.push((i, j, Self::pair_weight(&players[i], &players[j], context.scoring)));
+            }
+
+            if count % 2 == 1 {
+                edges.push((i, bye, Self::bye_weight(&players[i], context.scoring)));
+            }
+        }
+
+        let mate = max_weight_matching(&edges, true);
+        if mate.len() < count || mate[..count].iter().any(|m| m.is_none()) {
+            return Err(PairingError::NoLegalPairing);
+        }
+
+        let mut tables = (0..count)
+            .filter_map(|i| match mate[i] {
+                Some(j) if j == bye => Some((i, None)),
+                Some(j) if i < j => {
+                    // whoever has had white less often gets it
+                    if players[j].colour_balance() < players[i].colour_balance() {
+                        Some((j, Some(i)))
+                    } else {
+                        Some((i, Some(j)))
+                    }
+                },
+                _ => None,
+            })
+            .collect::<Vec<(usize, Option<usize>)>>();
+
+        // top tables first and the bye last
+        let points = players
+            .iter()
+            .map(|p| p.caluculate_match_points(context.scoring))
+            .collect::<Vec<Points>>();
+        tables.sort_by_key(|&(p1, p2)| match p2 {
+            Some(p2) => std::cmp::Reverse(Some(Points::max(points[p1], points[p2]))),
+            None => std::cmp::Reverse(None),
+        });
+
+        Self::trace(players, &points, &tables, context, trace);
+
+        let mut slots = players.drain(..).map(Some).collect::<Vec<Option<Player>>>();
+        let pairings = tables
+            .into_iter()
+            .map(|(p1, p2)| {
+                let p1 = slots[p1].take().unwrap();
+                let p2 = p2.map(|p2| slots[p2].take().unwrap());
+                Pairing::new(p1, p2)
+            })
+            .collect();
+
+        Ok(pairings)
+    }
+}
+
+/// Every pairing engine a tournament can be set to use
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum PairingSystem {
+    #[default]
+    Greedy,
+    Matching,
+    Dutch,
+    RoundRobin,
+    Pods,
+}
+
+impl PairingSystem {
+    fn engine(self) -> &'static dyn PairingEngine {
+        match self {
+            Self::Greedy => &GreedyEngine,
+            Self::Matching => &MatchingEngine,
+            Self::Dutch => &DutchEngine,
+            Self::RoundRobin => &RoundRobinEngine,
+            Self::Pods => &PodsEngine,
+        }
+    }
+}
+
+impl TryFrom<u8> for PairingSystem {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Greedy,
+            1 => Self::Matching,
+            2 => Self::Dutch,
+            3 => Self::RoundRobin,
+            4 => Self::Pods,
+            e => return Err(e),
+        })
+    }
+}
+
+impl PairingEngine for PairingSystem {
+    fn name(&self) -> &'static str {
+        self.engine().name()
+    }
+
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, rng: &mut dyn RngCore, trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError> {
+        self.engine().pair(players, context, rng, trace)
+    }
+
+    fn picks_bye(&self) -> bool {
+        self.engine().picks_bye()
+    }
+
+    fn seat(&self, players: &[Player], round: u16) -> Option<Seating> {
+        self.engine().seat(players, round)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swiss::{ByeValue, Colour, Outcome, BYE_PLAYER_NUMBER};
+
+    const CONTEXT: PairingContext = PairingContext {
+        scoring: crate::DEFUALT_SCORING,
+        rematches: RematchPolicy::AllowIfUnavoidable,
+        separate_tags: false,
+        tag_exceptions: &[],
+        round: 0,
+        seating: None,
+    };
+
+    fn generate_players(number: u16) -> Vec<Player> {
+        (1..number+1).map(|num| Player::new(num.to_string(), num)).collect()
+    }
+
+    /// plays a round where the first player of every pairing wins
+    fn play_round(engine: &dyn PairingEngine, players: Vec<Player>, context: &PairingContext) -> Vec<Player> {
+        let mut players = players;
+        let mut matches = engine.pair(&mut players, context, &mut rand::rng(), &mut PairingTrace::default()).unwrap();
+        for m in &mut matches {
+            m.give_outcome(Outcome::Win);
+        }
+
+        matches.into_iter()
+            .flat_map(|e| {
+                let (p1, p2) = e.extract_players();
+                [Some(p1), p2]
+            })
+            .flatten()
+            .collect()
+    }
+
+    #[test]
+    fn matching_avoids_repeat_byes() {
+        let context = PairingContext {
+            scoring: ScoreConfig {
+                bye: ByeValue::Points(Points::ZERO),
+                ..crate::DEFUALT_SCORING
+            },
+            ..CONTEXT
+        };
+        // everyone is on zero points so only the bye already given tells them apart
+        let mut players = generate_players(3);
+        players[0].add_match(BYE_PLAYER_NUMBER, Outcome::Bye, None);
+
+        for _ in 0..10 {
+            let mut field = players.clone();
+            let matches = MatchingEngine.pair(&mut field, &context, &mut rand::rng(), &mut PairingTrace::default()).unwrap();
+            let bye = matches.iter().find(|m| m.is_bye()).unwrap();
+            assert_ne!(bye.get_players().0.get_number(), 1);
+        }
+    }
+
+    #[test]
+    fn winners_play_winners() {
+        let players = play_round(&MatchingEngine, generate_players(8), &CONTEXT);
+        let mut players = players;
+        let matches = MatchingEngine.pair(&mut players, &CONTEXT, &mut rand::rng(), &mut PairingTrace::default()).unwrap();
+
+        for m in &matches {
+            let (p1, p2) = m.get_players();
+            assert_eq!(p1.extract_record(), p2.unwrap().extract_record());
+        }
+    }
+
+    #[test]
+    fn no_rematches_or_double_byes() {
+        let mut players = generate_players(5);
+        for _ in 0..5 {
+            players = play_round(&MatchingEngine, players, &CONTEXT);
+        }
+
+        for player in &players {
+            let byes = player.get_matches().iter().filter(|&&(opp, _)| opp == 0).count();
+            assert_eq!(byes, 1);
+
+            let mut opponents = player.get_matches().iter().map(|&(opp, _)| opp).collect::<Vec<u16>>();
+            opponents.sort();
+            opponents.dedup();
+            assert_eq!(opponents.len(), 5);
+        }
+    }
+
+    #[test]
+    fn impossible_without_rematch() {
+        let context = PairingContext {
+            rematches: RematchPolicy::Never,
+            ..CONTEXT
+        };
+
+        let mut players = play_round(&MatchingEngine, generate_players(2), &context);
+        assert_eq!(MatchingEngine.pair(&mut players, &context, &mut rand::rng(), &mut PairingTrace::default()), Err(PairingError::NoLegalPairing));
+        assert_eq!(players.len(), 2);
+    }
+
+    #[test]
+    fn colours_alternate() {
+        let mut players = play_round(&MatchingEngine, generate_players(2), &CONTEXT);
+        let matches = MatchingEngine.pair(&mut players, &CONTEXT, &mut rand::rng(), &mut PairingTrace::default()).unwrap();
+
+        let (white, black) = matches[0].get_players();
+        assert_eq!(white.get_colours(), &[Colour::Black]);
+        assert_eq!(black.unwrap().get_colours(), &[Colour::White]);
+    }
+
+    #[test]
+    fn systems_pick_engines() {
+        assert_eq!(PairingSystem::Greedy.name(), GreedyEngine.name());
+        assert_eq!(PairingSystem::Matching.name(), MatchingEngine.name());
+        assert_eq!(PairingSystem::Dutch.name(), DutchEngine.name());
+        assert_eq!(PairingSystem::RoundRobin.name(), RoundRobinEngine.name());
+        assert_eq!(PairingSystem::Pods.name(), PodsEngine.name());
+    }
+}
@@ -0,0 +1,157 @@
+//! Every change made to a [`Tournament`] is kept as an [`Event`] so it can be rolled back.
+//! Rounds are paired from the tournaments seed so replaying the same events from the same
+//! starting point always ends in the same state.
+
+use crate::engine::PairingSystem;
+use crate::player::Player;
+use crate::swiss::{Colour, GameScore, Outcome, RematchPolicy, ScoreConfig, TagPolicy};
+use crate::tiebreakers::TiebreakerKind;
+use crate::tournament::Tournament;
+
+/// A single change to a tournament
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    AddPlayer(Player),
+    DropPlayer(u16),
+    UndropPlayer(u16),
+    StartRound,
+    RepairRound(bool),
+    SwapSeats((usize, Colour), (usize, Colour)),
+    CreatePairing(u16, Option<u16>),
+    DeletePairing(usize),
+    ReportMatch(usize, Outcome),
+    ReportGames(usize, GameScore),
+    FinalizeRound,
+    StartTopCut(usize),
+    StartDoubleElimination(bool),
+    SetScoring(ScoreConfig),
+    SetRematchPolicy(RematchPolicy),
+    SetPairingSystem(PairingSystem),
+    SetSeed(u64),
+    SetTiebreakers(Vec<TiebreakerKind>),
+    SetTagPolicy(TagPolicy),
+    SetPlayerTag(u16, Option<String>),
+    SetAuditing(bool),
+}
+
+/// Append only list of the events applied to a tournament along with the ones undone
+/// that can still be redone. Applying a new event throws the undone ones away
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+    /// the tournament before the first event, taken when that event is applied
+    start: Option<Box<Tournament>>,
+    events: Vec<Event>,
+    undone: Vec<Event>,
+}
+
+impl Journal {
+    pub fn get_events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// events that were undone, the next one to be redone last
+    pub fn get_undone(&self) -> &[Event] {
+        &self.undone
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.events.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    pub(crate) fn get_start(&self) -> Option<&Tournament> {
+        self.start.as_deref()
+    }
+
+    pub(crate) fn set_start(&mut self, tournament: Tournament) {
+        self.start = Some(Box::new(tournament));
+    }
+
+    pub(crate) fn record(&mut self, event: Event) {
+        self.events.push(event);
+        self.undone.clear();
+    }
+
+    /// moves events past the first `count` over to be redone
+    pub(crate) fn rewind(&mut self, count: usize) {
+        while self.events.len() > count {
+            self.undone.push(self.events.pop().unwrap());
+        }
+    }
+
+    /// moves the last undone event back to the applied ones
+    pub(crate) fn forward(&mut self) {
+        if let Some(event) = self.undone.pop() {
+            self.events.push(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tournament::TournamentError;
+
+    fn tournament() -> Tournament {
+        let players = (1..=6).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Undo".to_string(), players);
+        tournament.set_seed(99);
+        tournament
+    }
+
+    #[test]
+    fn undo_finished_round() {
+        let mut tournament = tournament();
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::Win).unwrap();
+        tournament.report_match(1, Outcome::Win).unwrap();
+        tournament.report_match(2, Outcome::Loss).unwrap();
+        let reported = tournament.clone();
+
+        tournament.finilze_round().unwrap();
+        let finished = tournament.clone();
+        tournament.undo().unwrap();
+        assert_eq!(tournament, reported);
+        assert_eq!(tournament.get_pairings().len(), 3);
+
+        // the wrong result can be fixed and the round finished again
+        tournament.undo().unwrap();
+        assert_eq!(tournament.get_pairing(2).get_outcome(), None);
+        tournament.redo().unwrap();
+        tournament.redo().unwrap();
+        assert_eq!(tournament, finished);
+        assert!(matches!(tournament.redo(), Err(TournamentError::NothingToRedo)));
+    }
+
+    #[test]
+    fn roll_back_to_any_point() {
+        let mut tournament = tournament();
+        let start = tournament.clone();
+        for _ in 0..2 {
+            tournament.start_round().unwrap();
+            for idx in 0..3 {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+        tournament.drop_player(1).unwrap();
+        assert_eq!(tournament.get_journal().get_events().len(), 12);
+
+        tournament.undo_to(6).unwrap();
+        assert_eq!(tournament.get_round_number(), 1);
+        assert!(!tournament.is_dropped(1));
+        assert_eq!(tournament.get_journal().get_undone().len(), 6);
+
+        // anything new throws away what could have been redone
+        tournament.add_player(Player::new("Late".to_string(), 7));
+        assert!(!tournament.get_journal().can_redo());
+
+        tournament.undo_to(1).unwrap();
+        assert_eq!(tournament, start);
+        tournament.undo().unwrap();
+        assert!(matches!(tournament.undo(), Err(TournamentError::NothingToUndo)));
+    }
+}
@@ -1,11 +1,32 @@
+pub mod audit;
+pub mod backup;
+pub mod dutch;
+pub mod elimination;
+pub mod engine;
+pub mod journal;
+mod matching;
 pub mod player;
+pub mod round_robin;
+pub mod standings;
 pub mod swiss;
+pub mod tiebreakers;
 pub mod tournament;
 
 pub const DEFUALT_SCORING: swiss::ScoreConfig = swiss::ScoreConfig {
-    win: 3,
-    tie: 1,
-    loss: 0,
+    win: swiss::Points::new(3),
+    tie: swiss::Points::new(1),
+    loss: swiss::Points::ZERO,
+    bye: swiss::ByeValue::Win,
+    double_loss: swiss::Points::ZERO,
+};
+
+/// one point for a win and half a point for a draw
+pub const CHESS_SCORING: swiss::ScoreConfig = swiss::ScoreConfig {
+    win: swiss::Points::new(1),
+    tie: swiss::Points::HALF,
+    loss: swiss::Points::ZERO,
+    bye: swiss::ByeValue::Win,
+    double_loss: swiss::Points::ZERO,
 };
 
 
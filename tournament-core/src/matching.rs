@@ -0,0 +1,693 @@
+//! Maximum weight matching on general graphs using Edmonds' blossom algorithm.
+//! This follows the O(n^3) primal-dual formulation described by Galil in
+//! "Efficient algorithms for finding maximum matching in graphs" and is laid out
+//! the same way as Joris van Rantwijk's public domain `mwmatching.py` so the two
+//! can be compared side by side. All weights are integers which keeps every dual
+//! variable integral.
+
+/// finds a matching with the largest total weight, if `max_cardinality` is set only
+/// matchings with the most edges possible are considered.
+/// `edges` are `(vertex, vertex, weight)` and the result holds every vertexs mate
+pub(crate) fn max_weight_matching(edges: &[(usize, usize, i64)], max_cardinality: bool) -> Vec<Option<usize>> {
+    if edges.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matcher = Matcher::new(edges);
+    matcher.solve(max_cardinality);
+
+    matcher
+        .mate
+        .iter()
+        .map(|&p| (p >= 0).then(|| matcher.endpoint[p as usize]))
+        .collect()
+}
+
+struct Matcher<'a> {
+    edges: &'a [(usize, usize, i64)],
+    nvertex: usize,
+    /// endpoint[p] is the vertex at endpoint p, edge k has endpoints 2k and 2k+1
+    endpoint: Vec<usize>,
+    /// endpoints of the edges leaving each vertex
+    neighbend: Vec<Vec<usize>>,
+    /// the remote endpoint of the matched edge or -1
+    mate: Vec<isize>,
+    /// 0 free, 1 S, 2 T, used for vertices and top level blossoms
+    label: Vec<i8>,
+    /// endpoint through which the label was gained or -1
+    labelend: Vec<isize>,
+    /// top level blossom each vertex belongs to
+    inblossom: Vec<usize>,
+    blossomparent: Vec<isize>,
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<isize>,
+    /// blossomendps[b][i] is the endpoint connecting child i to child i + 1
+    blossomendps: Vec<Vec<usize>>,
+    /// least slack edge to a different S blossom or -1
+    bestedge: Vec<isize>,
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    /// twice the dual variable of each vertex and blossom
+    dualvar: Vec<i64>,
+    allowedge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+fn wrap(index: isize, len: usize) -> usize {
+    index.rem_euclid(len as isize) as usize
+}
+
+impl<'a> Matcher<'a> {
+    fn new(edges: &'a [(usize, usize, i64)]) -> Self {
+        let nvertex = edges.iter().map(|&(i, j, _)| usize::max(i, j) + 1).max().unwrap_or(0);
+        let max_weight = edges.iter().map(|&(_, _, w)| w).max().unwrap_or(0).max(0);
+
+        let endpoint = (0..2 * edges.len())
+            .map(|p| if p % 2 == 0 { edges[p / 2].0 } else { edges[p / 2].1 })
+            .collect();
+
+        let mut neighbend = vec![Vec::new(); nvertex];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            neighbend[i].push(2 * k + 1);
+            neighbend[j].push(2 * k);
+        }
+
+        let mut dualvar = vec![max_weight; nvertex];
+        dualvar.resize(2 * nvertex, 0);
+
+        let mut blossombase = (0..nvertex as isize).collect::<Vec<isize>>();
+        blossombase.resize(2 * nvertex, -1);
+
+        Self {
+            edges,
+            nvertex,
+            endpoint,
+            neighbend,
+            mate: vec![-1; nvertex],
+            label: vec![0; 2 * nvertex],
+            labelend: vec![-1; 2 * nvertex],
+            inblossom: (0..nvertex).collect(),
+            blossomparent: vec![-1; 2 * nvertex],
+            blossomchilds: vec![Vec::new(); 2 * nvertex],
+            blossombase,
+            blossomendps: vec![Vec::new(); 2 * nvertex],
+            bestedge: vec![-1; 2 * nvertex],
+            blossombestedges: vec![None; 2 * nvertex],
+            unusedblossoms: (nvertex..2 * nvertex).collect(),
+            dualvar,
+            allowedge: vec![false; edges.len()],
+            queue: Vec::new(),
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, w) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * w
+    }
+
+    fn blossom_leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(b, &mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, b: usize, leaves: &mut Vec<usize>) {
+        if b < self.nvertex {
+            leaves.push(b);
+        } else {
+            for &t in &self.blossomchilds[b] {
+                self.collect_leaves(t, leaves);
+            }
+        }
+    }
+
+    /// labels vertex `w` and its top level blossom with `t` reached through endpoint `p`
+    fn assign_label(&mut self, w: usize, t: i8, p: isize) {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = -1;
+        self.bestedge[b] = -1;
+
+        if t == 1 {
+            let leaves = self.blossom_leaves(b);
+            self.queue.extend(leaves);
+        } else if t == 2 {
+            let base = self.blossombase[b] as usize;
+            let mate = self.mate[base];
+            self.assign_label(self.endpoint[mate as usize], 1, mate ^ 1);
+        }
+    }
+
+    /// walks back from `v` and `w` to find a new blossom, returns its base or -1
+    /// when an augmenting path was found instead
+    fn scan_blossom(&mut self, v: usize, w: usize) -> isize {
+        let mut path = Vec::new();
+        let mut base = -1;
+        let (mut v, mut w) = (v as isize, w as isize);
+
+        while v != -1 || w != -1 {
+            let mut b = self.inblossom[v as usize];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+
+            path.push(b);
+            self.label[b] = 5;
+
+            if self.labelend[b] == -1 {
+                v = -1;
+            } else {
+                v = self.endpoint[self.labelend[b] as usize] as isize;
+                b = self.inblossom[v as usize];
+                v = self.endpoint[self.labelend[b] as usize] as isize;
+            }
+
+            if w != -1 {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+
+        for b in path {
+            self.label[b] = 1;
+        }
+
+        base
+    }
+
+    /// creates a blossom with `base` closed by edge `k`
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (v, w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+
+        let b = self.unusedblossoms.pop().expect("there are never more than n blossoms");
+        self.blossombase[b] = base as isize;
+        self.blossomparent[b] = -1;
+        self.blossomparent[bb] = b as isize;
+
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossomparent[bv] = b as isize;
+            path.push(bv);
+            endps.push(self.labelend[bv] as usize);
+            bv = self.inblossom[self.endpoint[self.labelend[bv] as usize]];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+
+        while bw != bb {
+            self.blossomparent[bw] = b as isize;
+            path.push(bw);
+            endps.push((self.labelend[bw] ^ 1) as usize);
+            bw = self.inblossom[self.endpoint[self.labelend[bw] as usize]];
+        }
+
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        self.blossomchilds[b] = path.clone();
+        self.blossomendps[b] = endps;
+
+        for v in self.blossom_leaves(b) {
+            if self.label[self.inblossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.inblossom[v] = b;
+        }
+
+        let mut bestedgeto = vec![-1_isize; 2 * self.nvertex];
+        for &bv in &path {
+            let nblists = match self.blossombestedges[bv].take() {
+                Some(list) => vec![list],
+                None => self
+                    .blossom_leaves(bv)
+                    .into_iter()
+                    .map(|v| self.neighbend[v].iter().map(|p| p / 2).collect())
+                    .collect(),
+            };
+
+            for k in nblists.into_iter().flatten() {
+                let (i, j, _) = self.edges[k];
+                let j = if self.inblossom[j] == b { i } else { j };
+                let bj = self.inblossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (bestedgeto[bj] == -1 || self.slack(k) < self.slack(bestedgeto[bj] as usize))
+                {
+                    bestedgeto[bj] = k as isize;
+                }
+            }
+
+            self.bestedge[bv] = -1;
+        }
+
+        let best = bestedgeto
+            .into_iter()
+            .filter(|&k| k != -1)
+            .map(|k| k as usize)
+            .collect::<Vec<usize>>();
+
+        self.bestedge[b] = -1;
+        for &k in &best {
+            if self.bestedge[b] == -1 || self.slack(k) < self.slack(self.bestedge[b] as usize) {
+                self.bestedge[b] = k as isize;
+            }
+        }
+        self.blossombestedges[b] = Some(best);
+    }
+
+    /// splits blossom `b` back into its children
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        let childs = self.blossomchilds[b].clone();
+        for &s in &childs {
+            self.blossomparent[s] = -1;
+            if s < self.nvertex {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == 0 {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.blossom_leaves(s) {
+                    self.inblossom[v] = s;
+                }
+            }
+        }
+
+        // an expanded T blossom mid stage needs its children relabeled
+        if !endstage && self.label[b] == 2 {
+            let len = childs.len();
+            let endps = self.blossomendps[b].clone();
+            let entrychild = self.inblossom[self.endpoint[(self.labelend[b] ^ 1) as usize]];
+
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as isize;
+            let (jstep, endptrick) = if j & 1 == 1 {
+                j -= len as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+
+            let mut p = self.labelend[b] as usize;
+            while j != 0 {
+                let back = endps[wrap(j - endptrick as isize, len)];
+                self.label[self.endpoint[p ^ 1]] = 0;
+                self.label[self.endpoint[back ^ endptrick ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p as isize);
+
+                self.allowedge[back / 2] = true;
+                j += jstep;
+                p = endps[wrap(j - endptrick as isize, len)] ^ endptrick;
+
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+
+            let bv = childs[wrap(j, len)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p as isize;
+            self.labelend[bv] = p as isize;
+            self.bestedge[bv] = -1;
+
+            j += jstep;
+            while childs[wrap(j, len)] != entrychild {
+                let bv = childs[wrap(j, len)];
+                if self.label[bv] == 1 {
+                    j += jstep;
+                    continue;
+                }
+
+                let reached = self.blossom_leaves(bv).into_iter().find(|&v| self.label[v] != 0);
+                if let Some(v) = reached {
+                    self.label[v] = 0;
+                    let mate = self.mate[self.blossombase[bv] as usize];
+                    self.label[self.endpoint[mate as usize]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+
+        self.label[b] = -1;
+        self.labelend[b] = -1;
+        self.blossomchilds[b] = Vec::new();
+        self.blossomendps[b] = Vec::new();
+        self.blossombase[b] = -1;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = -1;
+        self.unusedblossoms.push(b);
+    }
+
+    /// swaps matched and unmatched edges on the path through blossom `b` from `v` to its base
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != b as isize {
+            t = self.blossomparent[t] as usize;
+        }
+        if t >= self.nvertex {
+            self.augment_blossom(t, v);
+        }
+
+        let len = self.blossomchilds[b].len();
+        let i = self.blossomchilds[b].iter().position(|&c| c == t).unwrap();
+        let mut j = i as isize;
+        let (jstep, endptrick) = if i & 1 == 1 {
+            j -= len as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+
+        while j != 0 {
+            j += jstep;
+            let t = self.blossomchilds[b][wrap(j, len)];
+            let p = self.blossomendps[b][wrap(j - endptrick as isize, len)] ^ endptrick;
+            if t >= self.nvertex {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+
+            j += jstep;
+            let t = self.blossomchilds[b][wrap(j, len)];
+            if t >= self.nvertex {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+
+            self.mate[self.endpoint[p]] = (p ^ 1) as isize;
+            self.mate[self.endpoint[p ^ 1]] = p as isize;
+        }
+
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    /// swaps matched and unmatched edges along the augmenting path through edge `k`
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.nvertex {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p as isize;
+
+                if self.labelend[bs] == -1 {
+                    break;
+                }
+
+                let t = self.endpoint[self.labelend[bs] as usize];
+                let bt = self.inblossom[t];
+                s = self.endpoint[self.labelend[bt] as usize];
+                let j = self.endpoint[(self.labelend[bt] ^ 1) as usize];
+                if bt >= self.nvertex {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.labelend[bt];
+                p = (self.labelend[bt] ^ 1) as usize;
+            }
+        }
+    }
+
+    fn solve(&mut self, max_cardinality: bool) {
+        for _ in 0..self.nvertex {
+            self.label.fill(0);
+            self.bestedge.fill(-1);
+            for b in self.nvertex..2 * self.nvertex {
+                self.blossombestedges[b] = None;
+            }
+            self.allowedge.fill(false);
+            self.queue.clear();
+
+            for v in 0..self.nvertex {
+                if self.mate[v] == -1 && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, -1);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let Some(v) = self.queue.pop() else {
+                        break;
+                    };
+
+                    for idx in 0..self.neighbend[v].len() {
+                        let p = self.neighbend[v][idx];
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] {
+                            continue;
+                        }
+
+                        let mut kslack = 0;
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= 0 {
+                                self.allowedge[k] = true;
+                            }
+                        }
+
+                        if self.allowedge[k] {
+                            if self.label[self.inblossom[w]] == 0 {
+                                self.assign_label(w, 2, (p ^ 1) as isize);
+                            } else if self.label[self.inblossom[w]] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base >= 0 {
+                                    self.add_blossom(base as usize, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.labelend[w] = (p ^ 1) as isize;
+                            }
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b] == -1 || kslack < self.slack(self.bestedge[b] as usize) {
+                                self.bestedge[b] = k as isize;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.bestedge[w] == -1 || kslack < self.slack(self.bestedge[w] as usize))
+                        {
+                            self.bestedge[w] = k as isize;
+                        }
+                    }
+                }
+
+                if augmented {
+                    break;
+                }
+
+                // no augmenting path so update the dual variables
+                let mut deltatype = -1;
+                let mut delta = 0;
+                let mut deltaedge = 0;
+                let mut deltablossom = 0;
+
+                if !max_cardinality {
+                    deltatype = 1;
+                    delta = *self.dualvar[..self.nvertex].iter().min().unwrap();
+                }
+
+                for v in 0..self.nvertex {
+                    if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != -1 {
+                        let d = self.slack(self.bestedge[v] as usize);
+                        if deltatype == -1 || d < delta {
+                            delta = d;
+                            deltatype = 2;
+                            deltaedge = self.bestedge[v] as usize;
+                        }
+                    }
+                }
+
+                for b in 0..2 * self.nvertex {
+                    if self.blossomparent[b] == -1 && self.label[b] == 1 && self.bestedge[b] != -1 {
+                        let d = self.slack(self.bestedge[b] as usize) / 2;
+                        if deltatype == -1 || d < delta {
+                            delta = d;
+                            deltatype = 3;
+                            deltaedge = self.bestedge[b] as usize;
+                        }
+                    }
+                }
+
+                for b in self.nvertex..2 * self.nvertex {
+                    if self.blossombase[b] >= 0
+                        && self.blossomparent[b] == -1
+                        && self.label[b] == 2
+                        && (deltatype == -1 || self.dualvar[b] < delta)
+                    {
+                        delta = self.dualvar[b];
+                        deltatype = 4;
+                        deltablossom = b;
+                    }
+                }
+
+                if deltatype == -1 {
+                    // largest possible matching found, one final update keeps the duals valid
+                    deltatype = 1;
+                    delta = self.dualvar[..self.nvertex].iter().min().unwrap().max(&0).to_owned();
+                }
+
+                for v in 0..self.nvertex {
+                    match self.label[self.inblossom[v]] {
+                        1 => self.dualvar[v] -= delta,
+                        2 => self.dualvar[v] += delta,
+                        _ => {},
+                    }
+                }
+
+                for b in self.nvertex..2 * self.nvertex {
+                    if self.blossombase[b] >= 0 && self.blossomparent[b] == -1 {
+                        match self.label[b] {
+                            1 => self.dualvar[b] += delta,
+                            2 => self.dualvar[b] -= delta,
+                            _ => {},
+                        }
+                    }
+                }
+
+                match deltatype {
+                    1 => break,
+                    2 => {
+                        self.allowedge[deltaedge] = true;
+                        let (i, j, _) = self.edges[deltaedge];
+                        let i = if self.label[self.inblossom[i]] == 0 { j } else { i };
+                        self.queue.push(i);
+                    },
+                    3 => {
+                        self.allowedge[deltaedge] = true;
+                        let (i, _, _) = self.edges[deltaedge];
+                        self.queue.push(i);
+                    },
+                    _ => self.expand_blossom(deltablossom, false),
+                }
+            }
+
+            if !augmented {
+                break;
+            }
+
+            for b in self.nvertex..2 * self.nvertex {
+                if self.blossomparent[b] == -1
+                    && self.blossombase[b] >= 0
+                    && self.label[b] == 1
+                    && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    fn weight_of(edges: &[(usize, usize, i64)], mate: &[Option<usize>]) -> (usize, i64) {
+        edges
+            .iter()
+            .filter(|&&(i, j, _)| mate[i] == Some(j))
+            .fold((0, 0), |(count, total), &(_, _, w)| (count + 1, total + w))
+    }
+
+    /// tries every matching, returns the best (edge count, weight)
+    fn brute_force(edges: &[(usize, usize, i64)], used: &mut Vec<bool>, from: usize, max_cardinality: bool) -> (usize, i64) {
+        let mut best = (0, 0);
+        for k in from..edges.len() {
+            let (i, j, w) = edges[k];
+            if used[i] || used[j] {
+                continue;
+            }
+
+            used[i] = true;
+            used[j] = true;
+            let (count, total) = brute_force(edges, used, k + 1, max_cardinality);
+            let candidate = (count + 1, total + w);
+            used[i] = false;
+            used[j] = false;
+
+            let better = if max_cardinality {
+                candidate > best
+            } else {
+                candidate.1 > best.1
+            };
+            if better {
+                best = candidate;
+            }
+        }
+
+        best
+    }
+
+    #[test]
+    fn small_known_graphs() {
+        // a single edge
+        assert_eq!(max_weight_matching(&[(0, 1, 1)], false), vec![Some(1), Some(0)]);
+        // the heavy middle edge beats the two light outer ones
+        assert_eq!(
+            max_weight_matching(&[(1, 2, 10), (2, 3, 11)], false),
+            vec![None, None, Some(3), Some(2)],
+        );
+        // but not when the most edges are required
+        assert_eq!(
+            max_weight_matching(&[(0, 1, 5), (1, 2, 11), (2, 3, 5)], true),
+            vec![Some(1), Some(0), Some(3), Some(2)],
+        );
+        // a blossom has to be formed and expanded
+        assert_eq!(
+            max_weight_matching(&[(1, 2, 8), (1, 3, 9), (2, 3, 10), (3, 4, 7), (1, 6, 5), (4, 5, 6)], false),
+            vec![None, Some(6), Some(3), Some(2), Some(5), Some(4), Some(1)],
+        );
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(0x5157);
+        for round in 0..2000 {
+            let nvertex = rng.random_range(2..9);
+            let mut edges = Vec::new();
+            for i in 0..nvertex {
+                for j in i + 1..nvertex {
+                    if rng.random_bool(0.6) {
+                        edges.push((i, j, rng.random_range(-5..30)));
+                    }
+                }
+            }
+            if edges.is_empty() {
+                continue;
+            }
+
+            for max_cardinality in [false, true] {
+                let mate = max_weight_matching(&edges, max_cardinality);
+                for (v, m) in mate.iter().enumerate() {
+                    if let Some(m) = m {
+                        assert_eq!(mate[*m], Some(v));
+                    }
+                }
+
+                let mut used = vec![false; nvertex];
+                let expected = brute_force(&edges, &mut used, 0, max_cardinality);
+                let found = weight_of(&edges, &mate);
+                if max_cardinality {
+                    assert_eq!(found, expected, "round {} edges {:?}", round, edges);
+                } else {
+                    assert_eq!(found.1, expected.1, "round {} edges {:?}", round, edges);
+                }
+            }
+        }
+    }
+}
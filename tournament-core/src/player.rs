@@ -1,13 +1,23 @@
-use crate::swiss::{ScoreConfig, Outcome}; 
+use crate::swiss::{Colour, GameScore, Points, ScoreConfig, Outcome, BYE_PLAYER_NUMBER};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     name: String,
     wins: u8,
     losses: u8,
     ties: u8,
+    byes: u8,
+    /// also counted in `losses`, they are only kept apart as they can be worth different points
+    double_losses: u8,
     player_number: u16,
     opponents: Vec<(u16, Outcome)>,
+    /// game scores lined up with `opponents`, `None` when only the match result is known
+    games: Vec<Option<GameScore>>,
+    /// colours of every game actually played, byes have no colour
+    colours: Vec<Colour>,
+    /// team, club or household, see [`crate::swiss::TagPolicy`]
+    tag: Option<String>,
 }
 
 impl Player {
@@ -17,19 +27,41 @@ impl Player {
             wins: 0,
             losses: 0,
             ties: 0,
+            byes: 0,
+            double_losses: 0,
             player_number,
             opponents: Vec::new(),
+            games: Vec::new(),
+            colours: Vec::new(),
+            tag: None,
         }
     }
 
+    pub fn with_tag(name: String, player_number: u16, tag: String) -> Self {
+        Self {
+            tag: Some(tag),
+            ..Self::new(name, player_number)
+        }
+    }
+
+    /// byes are counted from `opponents` rather than being part of `score`,
+    /// double losses are counted from `opponents` as well but are also part of the losses in `score`
     pub fn from_information(name: String, player_number: u16, score: (u8,u8,u8), opponents: Vec<(u16, Outcome)>) -> Self {
+        let count = |kind: Outcome| opponents.iter().filter(|&&(_, outcome)| outcome == kind).count() as u8;
+        let byes = count(Outcome::Bye);
+        let double_losses = count(Outcome::DoubleLoss);
         Self {
             name,
             player_number,
             wins: score.0,
             losses: score.1,
             ties: score.2,
+            byes,
+            double_losses,
+            games: vec![None; opponents.len()],
             opponents,
+            colours: Vec::new(),
+            tag: None,
         }
     }
 
@@ -41,8 +73,12 @@ impl Player {
         (self.wins, self.losses, self.ties)
     }
 
-    pub fn caluculate_match_points(&self, score_config: ScoreConfig) -> u8 {
-        (self.wins * score_config.win) + (self.losses * score_config.loss) + (self.ties * score_config.tie)
+    pub fn caluculate_match_points(&self, score_config: ScoreConfig) -> Points {
+        (score_config.win * self.wins as u32)
+            + (score_config.loss * (self.losses - self.double_losses) as u32)
+            + (score_config.double_loss * self.double_losses as u32)
+            + (score_config.tie * self.ties as u32)
+            + (score_config.bye_points() * self.byes as u32)
     }
 
     pub fn get_number(&self) -> u16 {
@@ -57,21 +93,103 @@ impl Player {
         &self.opponents
     }
 
+    pub fn has_played(&self, op_number: u16) -> bool {
+        self.opponents.iter().any(|&(opp, _)| opp == op_number)
+    }
+
+    /// late entries are recorded as losses against the bye so only count rounds that gave points,
+    /// histories from before byes were recorded explicitly hold them as wins against the bye
+    pub fn has_had_bye(&self) -> bool {
+        self.opponents
+            .iter()
+            .any(|&(opp, outcome)| opp == BYE_PLAYER_NUMBER && outcome != Outcome::Loss)
+    }
+
+    pub fn get_colours(&self) -> &[Colour] {
+        &self.colours
+    }
+
+    pub fn add_colour(&mut self, colour: Colour) {
+        self.colours.push(colour);
+    }
+
+    /// games as white minus games as black
+    pub fn colour_balance(&self) -> i32 {
+        self.colours
+            .iter()
+            .map(|&c| match c {
+                Colour::White => 1,
+                Colour::Black => -1,
+            })
+            .sum()
+    }
+
     pub fn get_name(&self) -> &str {
         &self.name
     }
 
+    pub fn get_tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+    }
+
+    /// true if both players have the same tag
+    pub fn shares_tag(&self, other: &Player) -> bool {
+        self.tag.is_some() && self.tag == other.tag
+    }
+
+    pub fn get_games(&self) -> &[Option<GameScore>] {
+        &self.games
+    }
+
+    /// sets the game score of an already recorded match, used when loading. A match that
+    /// was never recorded is left alone
+    pub(crate) fn set_games(&mut self, match_idx: usize, games: Option<GameScore>) {
+        if let Some(slot) = self.games.get_mut(match_idx) {
+            *slot = games;
+        }
+    }
+
     pub fn add_opponent(&mut self, op_number: u16, outcome: Outcome) {
+        self.add_match(op_number, outcome, None);
+    }
+
+    pub fn add_match(&mut self, op_number: u16, outcome: Outcome, games: Option<GameScore>) {
         match outcome {
-            Outcome::Win => self.wins += 1,
-            Outcome::Loss => self.losses += 1,
-            Outcome::Tie => self.ties += 1,
+            Outcome::Win | Outcome::ForfeitWin => self.wins += 1,
+            Outcome::Loss | Outcome::ForfeitLoss => self.losses += 1,
+            Outcome::Tie | Outcome::IntentionalDraw => self.ties += 1,
+            Outcome::Bye => self.byes += 1,
+            Outcome::DoubleLoss => {
+                self.losses += 1;
+                self.double_losses += 1;
+            },
         }
         self.opponents.push((op_number, outcome));
+        self.games.push(games);
     }
 
     /// (wins, losses, ties)
     pub fn get_record(&self) -> (u8, u8, u8) {
         (self.wins, self.losses, self.ties)
     }
+
+    pub fn get_byes(&self) -> u8 {
+        self.byes
+    }
+
+    pub fn get_double_losses(&self) -> u8 {
+        self.double_losses
+    }
+
+    /// draws agreed to by both players, these are also part of the ties in [`Player::get_record`]
+    pub fn get_intentional_draws(&self) -> u8 {
+        self.opponents
+            .iter()
+            .filter(|&&(_, outcome)| outcome == Outcome::IntentionalDraw)
+            .count() as u8
+    }
 }
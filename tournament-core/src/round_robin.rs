@@ -0,0 +1,349 @@
+//! Everyone plays everyone. Players are seated in order of player number when the first
+//! round is paired and scheduled with the circle (Berger) method, the tournament keeps the
+//! seating so the schedule never changes. The schedule is fixed so players sharing a tag
+//! are not kept apart and there is nothing for the engines to trace.
+
+use rand::RngCore;
+
+use crate::audit::PairingTrace;
+use crate::engine::{PairingContext, PairingEngine};
+use crate::player::Player;
+use crate::swiss::{Pairing, PairingError, RematchPolicy};
+
+/// the largest pod [`PodsEngine`] splits a field into
+pub const POD_SIZE: usize = 8;
+
+/// indexes of the players meeting in `round` of a round robin between `count` players,
+/// the first of each pair is white and `None` is the bye. One player stays fixed while
+/// the rest rotate around them, with an odd count the fixed seat is the bye so it rotates
+/// through everyone. Rounds past the last one start the cycle again
+pub fn berger_round(count: usize, round: usize) -> Vec<(usize, Option<usize>)> {
+    if count < 2 {
+        return (0..count).map(|i| (i, None)).collect();
+    }
+
+    let seats = count + count % 2;
+    let rotating = seats - 1;
+    let round = round % rotating;
+    let seat = |i: usize| (i + round) % rotating;
+
+    // the fixed seat alternates colours and everyone else keeps the side they sit on
+    let fixed = count.is_multiple_of(2).then_some(seats - 1);
+    let mut pairs = vec![match fixed {
+        Some(fixed) if round.is_multiple_of(2) => (seat(0), Some(fixed)),
+        Some(fixed) => (fixed, Some(seat(0))),
+        None => (seat(0), None),
+    }];
+    pairs.extend((1..seats / 2).map(|i| (seat(i), Some(seat(rotating - i)))));
+
+    // the bye last like every other engine
+    pairs.sort_by_key(|&(_, b)| b.is_none());
+    pairs
+}
+
+/// every round of a round robin between `count` players, see [`berger_round`]
+pub fn berger_schedule(count: usize) -> Vec<Vec<(usize, Option<usize>)>> {
+    let rounds = if count < 2 { 0 } else { count + count % 2 - 1 };
+    (0..rounds).map(|round| berger_round(count, round)).collect()
+}
+
+/// sizes of the pods a field is split into, as even as possible and none above [`POD_SIZE`]
+pub fn pod_sizes(count: usize) -> Vec<usize> {
+    let pods = count.div_ceil(POD_SIZE).max(1);
+    (0..pods).map(|pod| count / pods + usize::from(pod < count % pods)).collect()
+}
+
+/// The seats of a round robin, given out when it starts and kept for every round after so
+/// a player dropping leaves a gap in the schedule rather than reshuffling it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Seating {
+    /// the round the schedule started on
+    first_round: u16,
+    /// player numbers by seat, one list for each pod
+    pods: Vec<Vec<u16>>,
+}
+
+impl Seating {
+    pub fn new(first_round: u16, pods: Vec<Vec<u16>>) -> Self {
+        Self { first_round, pods }
+    }
+
+    pub fn get_first_round(&self) -> u16 {
+        self.first_round
+    }
+
+    pub fn get_pods(&self) -> &[Vec<u16>] {
+        &self.pods
+    }
+
+    fn is_seated(&self, number: u16) -> bool {
+        self.pods.iter().any(|pod| pod.contains(&number))
+    }
+}
+
+/// player numbers of `players` in seat order
+fn seat_order(players: &[Player]) -> Vec<u16> {
+    let mut numbers = players.iter().map(|p| p.get_number()).collect::<Vec<u16>>();
+    numbers.sort();
+    numbers
+}
+
+/// pairs `round` of the schedule, the opponent of a player who has dropped gets the bye.
+/// A second cycle is only started when rematches are allowed
+fn pair_seated(players: &mut Vec<Player>, seating: &Seating, round: u16, rematches: RematchPolicy) -> Result<Vec<Pairing>, PairingError> {
+    let round = round.saturating_sub(seating.first_round) as usize;
+    let finished = seating.pods.iter().any(|pod| round >= pod.len().saturating_sub(1) + pod.len() % 2);
+    // players who joined after the schedule was made have no seat in it
+    if (finished && rematches == RematchPolicy::Never) || players.iter().any(|p| !seating.is_seated(p.get_number())) {
+        return Err(PairingError::NoLegalPairing);
+    }
+
+    let mut pairings = Vec::new();
+    let mut byes = Vec::new();
+    for pod in &seating.pods {
+        for (white, black) in berger_round(pod.len(), round) {
+            let mut take = |seat: usize| {
+                let idx = players.iter().position(|p| p.get_number() == pod[seat])?;
+                Some(players.remove(idx))
+            };
+            match (take(white), black.and_then(take)) {
+                (Some(white), Some(black)) => pairings.push(Pairing::new(white, Some(black))),
+                (Some(player), None) | (None, Some(player)) => byes.push(Pairing::new(player, None)),
+                (None, None) => {},
+            }
+        }
+    }
+
+    // the byes last like every other engine
+    pairings.extend(byes);
+    Ok(pairings)
+}
+
+/// pairs with the seating the tournament kept, or seats the players now without one
+fn pair_engine(engine: &dyn PairingEngine, players: &mut Vec<Player>, context: &PairingContext) -> Result<Vec<Pairing>, PairingError> {
+    if players.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let seated;
+    let seating = match context.seating {
+        Some(seating) => seating,
+        None => {
+            seated = engine.seat(players, context.round).unwrap_or_default();
+            &seated
+        },
+    };
+
+    pair_seated(players, seating, context.round, context.rematches)
+}
+
+/// every player faces every other player once
+pub struct RoundRobinEngine;
+
+/// splits the field into pods of at most [`POD_SIZE`] by player number and runs a round
+/// robin inside each one. Every pod is part of the same tournament so the results all
+/// count towards the overall standings
+pub struct PodsEngine;
+
+impl PairingEngine for RoundRobinEngine {
+    fn name(&self) -> &'static str {
+        "Round Robin"
+    }
+
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, _rng: &mut dyn RngCore, _trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError> {
+        pair_engine(self, players, context)
+    }
+
+    fn picks_bye(&self) -> bool {
+        true
+    }
+
+    fn seat(&self, players: &[Player], round: u16) -> Option<Seating> {
+        Some(Seating::new(round, vec![seat_order(players)]))
+    }
+}
+
+impl PairingEngine for PodsEngine {
+    fn name(&self) -> &'static str {
+        "Round Robin Pods"
+    }
+
+    fn pair(&self, players: &mut Vec<Player>, context: &PairingContext, _rng: &mut dyn RngCore, _trace: &mut PairingTrace) -> Result<Vec<Pairing>, PairingError> {
+        pair_engine(self, players, context)
+    }
+
+    fn picks_bye(&self) -> bool {
+        true
+    }
+
+    fn seat(&self, players: &[Player], round: u16) -> Option<Seating> {
+        let mut numbers = seat_order(players).into_iter();
+        let pods = pod_sizes(players.len())
+            .into_iter()
+            .map(|size| numbers.by_ref().take(size).collect())
+            .collect();
+        Some(Seating::new(round, pods))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::PairingSystem;
+    use crate::swiss::Outcome;
+    use crate::tournament::Tournament;
+
+    fn meetings(schedule: &[Vec<(usize, Option<usize>)>], count: usize) -> Vec<Vec<usize>> {
+        let mut met = vec![vec![0; count + 1]; count];
+        for round in schedule {
+            for &(a, b) in round {
+                let b = b.unwrap_or(count);
+                met[a][b] += 1;
+                if b < count {
+                    met[b][a] += 1;
+                }
+            }
+        }
+        met
+    }
+
+    #[test]
+    fn everyone_meets_once() {
+        for count in 2..=11 {
+            let schedule = berger_schedule(count);
+            let met = meetings(&schedule, count);
+            for (a, row) in met.iter().enumerate() {
+                for (b, &times) in row[..count].iter().enumerate() {
+                    assert_eq!(times, usize::from(a != b), "{} players", count);
+                }
+                // the bye rotates so odd fields give everyone exactly one
+                assert_eq!(row[count], count % 2);
+            }
+
+            for round in &schedule {
+                let mut seen = round.iter().flat_map(|&(a, b)| [Some(a), b]).flatten().collect::<Vec<usize>>();
+                seen.sort();
+                assert_eq!(seen, (0..count).collect::<Vec<usize>>());
+            }
+        }
+    }
+
+    #[test]
+    fn pods_are_even() {
+        assert_eq!(pod_sizes(8), vec![8]);
+        assert_eq!(pod_sizes(9), vec![5, 4]);
+        assert_eq!(pod_sizes(20), vec![7, 7, 6]);
+        assert_eq!(pod_sizes(24), vec![8, 8, 8]);
+    }
+
+    #[test]
+    fn full_round_robin() {
+        let players = (1..=5).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("League".to_string(), players);
+        tournament.set_pairing_system(PairingSystem::RoundRobin);
+
+        for _ in 0..5 {
+            tournament.start_round().unwrap();
+            for idx in 0..tournament.get_pairings().len() - 1 {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        for player in tournament.get_players() {
+            let mut opponents = player.get_matches().iter().map(|m| m.0).collect::<Vec<u16>>();
+            opponents.sort();
+            let expected = (0..=5).filter(|&n| n != player.get_number()).collect::<Vec<u16>>();
+            assert_eq!(opponents, expected);
+        }
+        assert!(matches!(tournament.start_round(), Err(crate::tournament::TournamentError::Pairing(PairingError::NoLegalPairing))));
+        assert_eq!(tournament.get_players().len(), 5);
+    }
+
+    #[test]
+    fn pods_stay_apart() {
+        let players = (1..=16).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Draft".to_string(), players);
+        tournament.set_pairing_system(PairingSystem::Pods);
+
+        for _ in 0..3 {
+            tournament.start_round().unwrap();
+            for pairing in tournament.get_pairings() {
+                let (p1, p2) = pairing.get_players();
+                assert_eq!((p1.get_number() - 1) / 8, (p2.unwrap().get_number() - 1) / 8);
+            }
+            for idx in 0..tournament.get_pairings().len() {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        // one table of standings across both pods
+        assert_eq!(tournament.standings().len(), 16);
+    }
+
+    #[test]
+    fn drops_keep_the_schedule() {
+        let players = (1..=6).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("League".to_string(), players);
+        tournament.set_pairing_system(PairingSystem::RoundRobin);
+
+        for round in 0..5 {
+            if round == 2 {
+                tournament.drop_player(3).unwrap();
+            }
+            tournament.start_round().unwrap();
+            let tables = tournament.get_pairings().iter().filter(|p| !p.is_bye()).count();
+            for idx in 0..tables {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        // everyone still in met everyone else once, taking a bye where player 3 was due
+        for player in tournament.get_players() {
+            let mut opponents = player.get_matches().iter().map(|m| m.0).collect::<Vec<u16>>();
+            opponents.sort();
+            let mut expected = (1..=6).filter(|&n| n != player.get_number()).collect::<Vec<u16>>();
+            if !player.has_played(3) {
+                expected.retain(|&n| n != 3);
+                expected.insert(0, 0);
+            }
+            assert_eq!(opponents, expected, "player {}", player.get_number());
+        }
+        assert_eq!(tournament.get_seating().unwrap().get_pods(), [vec![1, 2, 3, 4, 5, 6]]);
+
+        // the seating is saved with the tournament
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+        assert_eq!(Tournament::read_from(&bytes[..]).unwrap(), tournament);
+    }
+
+    #[test]
+    fn drops_keep_pods() {
+        let players = (1..=16).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Draft".to_string(), players);
+        tournament.set_pairing_system(PairingSystem::Pods);
+
+        for round in 0..3 {
+            if round == 1 {
+                tournament.drop_player(2).unwrap();
+            }
+            tournament.start_round().unwrap();
+            for pairing in tournament.get_pairings() {
+                let (p1, p2) = pairing.get_players();
+                if let Some(p2) = p2 {
+                    assert_eq!((p1.get_number() - 1) / 8, (p2.get_number() - 1) / 8);
+                }
+            }
+            // one bye for whoever was due to play player 2
+            assert_eq!(tournament.get_pairings().iter().filter(|p| p.is_bye()).count(), usize::from(round > 0));
+            let tables = tournament.get_pairings().iter().filter(|p| !p.is_bye()).count();
+            for idx in 0..tables {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+    }
+}
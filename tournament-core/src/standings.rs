@@ -0,0 +1,103 @@
+use crate::player::Player;
+use crate::swiss::Points;
+use crate::tiebreakers::{compare_values, evaluate_chain, TiebreakContext, Tiebreaker};
+
+/// A single row of the standings table
+#[derive(Clone, Debug, PartialEq)]
+pub struct Standing {
+    /// 1 is first place, players that share a rank share the same number
+    pub rank: u16,
+    /// true when at least one other player has the exact same points and tiebreakers
+    pub shared: bool,
+    pub player_number: u16,
+    pub name: String,
+    /// (wins, losses, ties)
+    pub record: (u8, u8, u8),
+    pub match_points: Points,
+    /// values in the same order as the tiebreaker chain used
+    pub tiebreakers: Vec<f32>,
+}
+
+impl Standing {
+    fn same_position(&self, other: &Self) -> bool {
+        self.match_points == other.match_points && self.tiebreakers == other.tiebreakers
+    }
+}
+
+/// ranks every player in `context` by match points then each tiebreaker in `chain`,
+/// players that can not be separated share a rank and the next rank is skipped (1, 2, 2, 4)
+pub fn calculate_standings<T: Tiebreaker>(chain: &[T], context: &TiebreakContext) -> Vec<Standing> {
+    let mut values = evaluate_chain(chain, context);
+
+    let mut standings = context
+        .iter_players()
+        .map(|player: &Player| Standing {
+            rank: 0,
+            shared: false,
+            player_number: player.get_number(),
+            name: player.get_name().to_string(),
+            record: player.get_record(),
+            match_points: player.caluculate_match_points(context.scoring()),
+            tiebreakers: values.remove(&player.get_number()).unwrap_or_default(),
+        })
+        .collect::<Vec<Standing>>();
+
+    standings.sort_by(|a, b| {
+        b.match_points
+            .cmp(&a.match_points)
+            .then_with(|| compare_values(&b.tiebreakers, &a.tiebreakers))
+            .then_with(|| a.player_number.cmp(&b.player_number))
+    });
+
+    for idx in 0..standings.len() {
+        let rank = match idx {
+            0 => 1,
+            _ if standings[idx].same_position(&standings[idx - 1]) => {
+                standings[idx - 1].shared = true;
+                standings[idx].shared = true;
+                standings[idx - 1].rank
+            },
+            _ => idx as u16 + 1,
+        };
+        standings[idx].rank = rank;
+    }
+
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swiss::Outcome;
+    use crate::tiebreakers::TiebreakerKind;
+    use crate::DEFUALT_SCORING;
+
+    #[test]
+    fn shared_ranks_skip() {
+        // 1 beat 2, 3 beat 4, so 1 and 3 are identical as are 2 and 4
+        let players = vec![
+            Player::from_information("1".to_string(), 1, (1, 0, 0), vec![(2, Outcome::Win)]),
+            Player::from_information("2".to_string(), 2, (0, 1, 0), vec![(1, Outcome::Loss)]),
+            Player::from_information("3".to_string(), 3, (1, 0, 0), vec![(4, Outcome::Win)]),
+            Player::from_information("4".to_string(), 4, (0, 1, 0), vec![(3, Outcome::Loss)]),
+            Player::new("5".to_string(), 5),
+        ];
+        let context = TiebreakContext::new(&players, DEFUALT_SCORING);
+        let standings = calculate_standings(&[TiebreakerKind::OpponentMatchWin], &context);
+
+        let ranks = standings
+            .iter()
+            .map(|s| (s.player_number, s.rank, s.shared))
+            .collect::<Vec<(u16, u16, bool)>>();
+
+        assert_eq!(ranks, vec![
+            (1, 1, true),
+            (3, 1, true),
+            (2, 3, true),
+            (4, 3, true),
+            (5, 5, false),
+        ]);
+        assert_eq!(standings[0].match_points, Points::new(3));
+        assert_eq!(standings[0].tiebreakers.len(), 1);
+    }
+}
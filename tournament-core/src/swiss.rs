@@ -1,17 +1,67 @@
-use std::collections::{HashMap, hash_map::Entry};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
 
+use rand::Rng;
 use rand::seq::SliceRandom;
 
+use crate::audit::{Float, FloatReason, PairingTrace, ScoreBracket};
+use crate::matching::max_weight_matching;
 use crate::player::Player;
 
-const BYE_PLAYER_NUMBER: u16 = 0;
+pub const BYE_PLAYER_NUMBER: u16 = 0;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Outcome {
     Win,
     Loss,
-    Tie
+    /// an unintentional draw, such as the match going to time
+    Tie,
+    /// given to the player left without an opponent, see [`ByeValue`] for its worth
+    Bye,
+    /// both players were given a loss, worth [`ScoreConfig::double_loss`]
+    DoubleLoss,
+    /// a draw both players agreed to, scored the same as a [`Outcome::Tie`]
+    IntentionalDraw,
+    /// the opponent never showed up, scored as a win but left out of game win percentage
+    ForfeitWin,
+    ForfeitLoss,
+}
+
+impl Outcome {
+    /// draws of either kind
+    pub fn is_draw(self) -> bool {
+        matches!(self, Self::Tie | Self::IntentionalDraw)
+    }
+
+    pub fn is_forfeit(self) -> bool {
+        matches!(self, Self::ForfeitWin | Self::ForfeitLoss)
+    }
+
+    /// outcomes that leave a winner, which elimination matches need
+    pub fn has_winner(self) -> bool {
+        matches!(self, Self::Win | Self::Loss | Self::ForfeitWin | Self::ForfeitLoss)
+    }
+}
+
+/// the reverse of `outcome as u8`, giving back the byte when it is not an outcome
+impl TryFrom<u8> for Outcome {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Win,
+            1 => Self::Loss,
+            2 => Self::Tie,
+            3 => Self::Bye,
+            4 => Self::DoubleLoss,
+            5 => Self::IntentionalDraw,
+            6 => Self::ForfeitWin,
+            7 => Self::ForfeitLoss,
+            e => return Err(e),
+        })
+    }
 }
 
 impl std::ops::Not for Outcome {
@@ -21,24 +71,109 @@ impl std::ops::Not for Outcome {
             Self::Win => Self::Loss,
             Self::Loss => Self::Win,
             Self::Tie => Self::Tie,
+            Self::Bye => Self::Bye,
+            Self::DoubleLoss => Self::DoubleLoss,
+            Self::IntentionalDraw => Self::IntentionalDraw,
+            Self::ForfeitWin => Self::ForfeitLoss,
+            Self::ForfeitLoss => Self::ForfeitWin,
         }
     }
 }
 
-// Add multi round match support i.e win win -> Winner
-#[derive(Debug, PartialEq)]
+/// Games won, lost and drawn within a single match from one player's side
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameScore {
+    pub wins: u8,
+    pub losses: u8,
+    pub draws: u8,
+}
+
+impl GameScore {
+    pub const fn new(wins: u8, losses: u8, draws: u8) -> Self {
+        Self {
+            wins,
+            losses,
+            draws,
+        }
+    }
+
+    /// whoever won more games won the match
+    pub fn outcome(&self) -> Outcome {
+        match self.wins.cmp(&self.losses) {
+            std::cmp::Ordering::Greater => Outcome::Win,
+            std::cmp::Ordering::Less => Outcome::Loss,
+            std::cmp::Ordering::Equal => Outcome::Tie,
+        }
+    }
+
+    pub fn games(&self) -> u8 {
+        self.wins + self.losses + self.draws
+    }
+}
+
+/// the same score from the opponent's side
+impl std::ops::Not for GameScore {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        Self {
+            wins: self.losses,
+            losses: self.wins,
+            draws: self.draws,
+        }
+    }
+}
+
+/// 2-1 or 1-1-1, draws are only shown when there were some
+impl Display for GameScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.wins, self.losses)?;
+        if self.draws > 0 {
+            write!(f, "-{}", self.draws)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Which side of the board a player sat, the first player of a pairing is always white
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum Colour {
+    White,
+    Black,
+}
+
+impl std::ops::Not for Colour {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pairing {
     p1: Player,
     p2: Option<Player>,
-    winner: Option<Outcome>
+    winner: Option<Outcome>,
+    /// individual games from the side of p1 when they were reported
+    games: Option<GameScore>,
 }
 
 impl Pairing {
+    /// a pairing without a second player is a bye and is declared straight away
     pub const fn new(p1: Player, p2: Option<Player>) -> Self {
+        let winner = if p2.is_none() { Some(Outcome::Bye) } else { None };
         Self {
             p1,
             p2,
-            winner: None,
+            winner,
+            games: None,
         }
     }
 
@@ -46,8 +181,32 @@ impl Pairing {
         self.winner
     }
 
+    /// byes are always recorded as a bye so giving one an outcome does nothing
     pub fn give_outcome(&mut self, outcome: Outcome) {
+        if self.is_bye() {
+            return;
+        }
+
         self.winner = Some(outcome);
+        self.games = None;
+    }
+
+    pub fn get_games(&self) -> Option<GameScore> {
+        self.games
+    }
+
+    /// records every game from the side of the first player, the match outcome follows from them
+    pub fn give_games(&mut self, games: GameScore) {
+        if self.is_bye() {
+            return;
+        }
+
+        self.winner = Some(games.outcome());
+        self.games = Some(games);
+    }
+
+    pub fn is_bye(&self) -> bool {
+        self.p2.is_none()
     }
 
     pub fn is_delcared(&self) -> bool {
@@ -60,8 +219,10 @@ impl Pairing {
         }
 
         if let Some(p2) = &mut self.p2 {
-            self.p1.add_opponent(p2.get_number(), self.winner.unwrap());
-            p2.add_opponent(self.p1.get_number(), !self.winner.unwrap());
+            self.p1.add_match(p2.get_number(), self.winner.unwrap(), self.games);
+            p2.add_match(self.p1.get_number(), !self.winner.unwrap(), self.games.map(|g| !g));
+            self.p1.add_colour(Colour::White);
+            p2.add_colour(Colour::Black);
         } else {
             self.p1.add_opponent(BYE_PLAYER_NUMBER, self.winner.unwrap());
         }
@@ -69,6 +230,11 @@ impl Pairing {
         (self.p1, self.p2)
     }
 
+    /// hands the players back without recording the result in their history
+    pub fn into_players(self) -> (Player, Option<Player>) {
+        (self.p1, self.p2)
+    }
+
     pub fn pretty_print(&self) {
         let (p1_w, p1_l, p1_t) = self.p1.extract_record();
         print!("{} ({}/{}/{})", self.p1.get_name(), p1_w, p1_l, p1_t);
@@ -84,101 +250,442 @@ impl Pairing {
     pub fn get_players(&self) -> (&Player, Option<&Player>) {
         (&self.p1, self.p2.as_ref())
     }
+
+    /// the player sat on `colour`, byes have nobody on black
+    pub fn get_seat(&self, colour: Colour) -> Option<&Player> {
+        match colour {
+            Colour::White => Some(&self.p1),
+            Colour::Black => self.p2.as_ref(),
+        }
+    }
+
+    pub(crate) fn get_seat_mut(&mut self, colour: Colour) -> Option<&mut Player> {
+        match colour {
+            Colour::White => Some(&mut self.p1),
+            Colour::Black => self.p2.as_mut(),
+        }
+    }
+
+    /// swaps who plays white, byes stay as they are
+    pub(crate) fn swap_sides(&mut self) {
+        if let Some(p2) = &mut self.p2 {
+            std::mem::swap(&mut self.p1, p2);
+        }
+    }
+
+    /// forgets the reported result, used when the players in the pairing change
+    pub(crate) fn clear_result(&mut self) {
+        if !self.is_bye() {
+            self.winner = None;
+        }
+        self.games = None;
+    }
+}
+
+/// Match points, kept in halves so chess style half points need no floats
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Points(u32);
+
+impl Points {
+    pub const ZERO: Self = Self(0);
+    pub const HALF: Self = Self(1);
+
+    pub const fn new(whole: u32) -> Self {
+        Self(whole * 2)
+    }
+
+    pub const fn from_halves(halves: u32) -> Self {
+        Self(halves)
+    }
+
+    pub const fn halves(self) -> u32 {
+        self.0
+    }
+
+    pub fn as_f32(self) -> f32 {
+        self.0 as f32 / 2.0
+    }
+}
+
+impl std::ops::Add for Points {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl std::ops::Mul<u32> for Points {
+    type Output = Self;
+    fn mul(self, rhs: u32) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
+impl std::iter::Sum for Points {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |a, b| a + b)
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// 3 or 1.5
+impl Display for Points {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0 / 2)?;
+        if self.0 % 2 == 1 {
+            write!(f, ".5")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScoreConfig {
-    pub win: u8,
-    pub loss: u8,
-    pub tie: u8,
+    pub win: Points,
+    pub loss: Points,
+    pub tie: Points,
+    pub bye: ByeValue,
+    /// what each player gets when both are given a loss
+    pub double_loss: Points,
+}
+
+/// What a bye is worth
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ByeValue {
+    /// the same as winning a match
+    #[default]
+    Win,
+    /// the same as tieing a match
+    Tie,
+    Points(Points),
+}
+
+impl Default for ScoreConfig {
+    fn default() -> Self {
+        crate::DEFUALT_SCORING
+    }
 }
 
-pub fn generate_pairings(players: &mut Vec<Player>, scoring: ScoreConfig) -> Vec<Pairing> {
-    let mut map: HashMap<u8, Vec<Player>> = HashMap::new();
-    let mut pairings = Vec::new();
-    let mut max_mp = 0;
+impl ScoreConfig {
+    /// match points earned for a single result
+    pub fn points_for(&self, outcome: Outcome) -> Points {
+        match outcome {
+            Outcome::Win | Outcome::ForfeitWin => self.win,
+            Outcome::Loss | Outcome::ForfeitLoss => self.loss,
+            Outcome::Tie | Outcome::IntentionalDraw => self.tie,
+            Outcome::Bye => self.bye_points(),
+            Outcome::DoubleLoss => self.double_loss,
+        }
+    }
+
+    pub fn bye_points(&self) -> Points {
+        match self.bye {
+            ByeValue::Win => self.win,
+            ByeValue::Tie => self.tie,
+            ByeValue::Points(points) => points,
+        }
+    }
+}
+
+/// How to handle two players who have already met
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RematchPolicy {
+    /// fail to pair rather than pair a rematch
+    #[default]
+    Never,
+    /// only pair a rematch when every other pairing is impossible
+    AllowIfUnavoidable,
+}
+
+/// How to keep apart players sharing a tag, such as a team or household
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TagPolicy {
+    /// tags make no difference to pairings
+    #[default]
+    Ignore,
+    /// players sharing a tag are never paired, rounds that can not be paired that way fail
+    Never,
+    /// players sharing a tag are kept apart before this round, counting from 1,
+    /// unless there is no other way to pair the round
+    AvoidUntil(u16),
+}
 
-    while let Some(player) = players.pop() {
+impl TagPolicy {
+    /// if players sharing a tag are kept apart `finished` rounds in
+    pub fn separates(self, finished: u16) -> bool {
+        match self {
+            Self::Ignore => false,
+            Self::Never => true,
+            Self::AvoidUntil(round) => finished + 1 < round,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PairingError {
+    /// rematches, tags, the schedule or the byes already given left no way to pair the round
+    NoLegalPairing,
+}
+
+impl Display for PairingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoLegalPairing => write!(f, "No legal pairing exists for this round"),
+        }
+    }
+}
+
+impl Error for PairingError {}
+
+/// pairs players in the highest score bracket first, players who can not be
+/// paired in their bracket float down to the next one. Brackets are shuffled with `rng`
+/// so the same rng state always gives the same pairings. On error the players are
+/// handed back untouched
+pub fn generate_pairings<R: Rng + ?Sized>(players: &mut Vec<Player>, scoring: ScoreConfig, rematches: RematchPolicy, rng: &mut R) -> Result<Vec<Pairing>, PairingError> {
+    generate_pairings_with(players, scoring, rematches, |_, _| true, rng, &mut PairingTrace::default())
+}
+
+/// [`generate_pairings`] where every pair must also be `allowed`, rematches are still
+/// only paired as `rematches` allows. How the pairings were found goes in `trace`
+pub fn generate_pairings_with<R, F>(
+    players: &mut Vec<Player>,
+    scoring: ScoreConfig,
+    rematches: RematchPolicy,
+    allowed: F,
+    rng: &mut R,
+    trace: &mut PairingTrace,
+) -> Result<Vec<Pairing>, PairingError>
+where
+    R: Rng + ?Sized,
+    F: Fn(&Player, &Player) -> bool,
+{
+    let mut map: HashMap<Points, Vec<Player>> = HashMap::new();
+
+    for player in players.drain(..) {
         let match_points = player.caluculate_match_points(scoring);
-        max_mp = std::cmp::max(match_points, max_mp);
-        let e = map.entry(match_points).or_default();
-        e.push(player)
-    }
-    
-    let mut left_overs: [Option<Player>; 2] = [None, None];
-
-    loop {
-        let mut players = match map.remove(&max_mp) {
-            Some(e) => e,
-            None => {
-                if max_mp == 0 {
-                    break;
-                } else {
-                    max_mp -= 1;
-                    continue;
-                }
-            },
+        map.entry(match_points).or_default().push(player);
+    }
+
+    let mut brackets = map.keys().copied().collect::<Vec<Points>>();
+    brackets.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut ordered = Vec::new();
+    for bracket in brackets {
+        let mut bracket_players = map.remove(&bracket).unwrap();
+        bracket_players.shuffle(rng);
+        ordered.append(&mut bracket_players);
+    }
+
+    let fresh = |p1: &Player, p2: &Player| !p1.has_played(p2.get_number()) && allowed(p1, p2);
+    let mut found = find_pairings(&ordered, fresh).map(|found| (found, true));
+    if found.is_none() && rematches == RematchPolicy::AllowIfUnavoidable {
+        found = find_pairings(&ordered, &allowed).map(|found| (found, false));
+    }
+
+    let Some((found, avoided_rematches)) = found else {
+        players.append(&mut ordered);
+        return Err(PairingError::NoLegalPairing);
+    };
+
+    if avoided_rematches {
+        trace_pairings(&ordered, &found, scoring, &fresh, &allowed, trace);
+    } else {
+        trace_pairings(&ordered, &found, scoring, &allowed, &allowed, trace);
+    }
+
+    let mut slots = ordered.into_iter().map(Some).collect::<Vec<Option<Player>>>();
+    let pairings = found
+        .into_iter()
+        .map(|(p1, p2)| {
+            let p1 = slots[p1].take().unwrap();
+            let p2 = p2.map(|p2| slots[p2].take().unwrap());
+            Pairing::new(p1, p2)
+        })
+        .collect();
+
+    Ok(pairings)
+}
+
+/// follows [`find_pairings`] through the pairs it took, `rule` being the test it paired
+/// with. Each player was offered everyone below them in order so those passed over for
+/// having already played are the rematches avoided
+fn trace_pairings(
+    players: &[Player],
+    found: &[(usize, Option<usize>)],
+    scoring: ScoreConfig,
+    rule: &dyn Fn(&Player, &Player) -> bool,
+    allowed: &dyn Fn(&Player, &Player) -> bool,
+    trace: &mut PairingTrace,
+) {
+    let points = players.iter().map(|p| p.caluculate_match_points(scoring)).collect::<Vec<Points>>();
+    for (player, &points) in players.iter().zip(&points) {
+        match trace.brackets.last_mut() {
+            Some(bracket) if bracket.points == points => bracket.players.push(player.get_number()),
+            _ => trace.brackets.push(ScoreBracket {
+                points,
+                players: vec![player.get_number()],
+            }),
+        }
+    }
+
+    let mut paired = vec![false; players.len()];
+    if let Some(&(bye, None)) = found.last() {
+        paired[bye] = true;
+        let mut byes = (0..players.len()).rev().collect::<Vec<usize>>();
+        byes.sort_by_key(|&i| players[i].has_had_bye());
+        trace.byes_tried = byes.into_iter().take_while(|&i| i != bye).map(|i| players[i].get_number()).collect();
+    }
+
+    for &(first, second) in found {
+        let Some(second) = second else {
+            continue;
         };
+        paired[first] = true;
 
-        
-        let mut rng = rand::rng();
-        players.shuffle(&mut rng);
-        
-        let (left_1, left_2) = (left_overs[0].take(), left_overs[1].take());
-
-        // we have 2 left overs can't play prev oponent
-        if let Some(left_over) = left_2 {
-            let p2 = players.pop().unwrap();
-            pairings.push(Pairing::new(left_over, Some(p2)));
-        }
-
-        if let Some(left_over) = left_1 {
-            players.push(left_over);
-        }
-
-        while players.len() > 1 {
-            let p1 = players.pop().unwrap();
-            let p2 = players.pop().unwrap();
-            if let Some((p1_prev, _)) = p1.get_last_opponent() {
-                if p1_prev == p2.get_number() && players.is_empty() {
-                    left_overs[0] = Some(p1);
-                    left_overs[1] = Some(p2);
-                    break;
-                } else if p1_prev == p2.get_number() && !players.is_empty() {
-                    let p3 = players.pop().unwrap();
-                    pairings.push(Pairing::new(p1, Some(p3)));
-                    players.push(p2);
-                    continue;
-                }
+        let (player, number) = (&players[first], players[first].get_number());
+        for (other, _) in players.iter().enumerate().take(second).skip(first + 1).filter(|&(i, _)| !paired[i]) {
+            if !rule(player, &players[other]) && allowed(player, &players[other]) {
+                trace.rematches_avoided.push((number, players[other].get_number()));
             }
-            pairings.push(Pairing::new(p1, Some(p2)));
         }
 
-        if !players.is_empty() {
-            left_overs[0] = players.pop();
+        if points[second] < points[first] {
+            let bracket = (0..players.len())
+                .filter(|&i| !paired[i] && points[i] == points[first])
+                .collect::<Vec<usize>>();
+            let reason = if bracket.is_empty() {
+                FloatReason::OddBracket
+            } else if !bracket.iter().any(|&i| rule(player, &players[i])) {
+                FloatReason::NoOpponentInBracket
+            } else {
+                FloatReason::PairingTheRest
+            };
+            trace.floats.push(Float {
+                player: number,
+                opponent: players[second].get_number(),
+                from: points[first],
+                to: points[second],
+                reason,
+            });
         }
+        paired[second] = true;
     }
+}
 
-    // will happen in the case that only 2 players on round 3
-    assert_eq!(left_overs[1], None);
+/// indexes into `players` of each pairing, `None` being the bye.
+/// players earlier in the slice are paired first and the bye goes to the
+/// latest player that still leaves a legal pairing, a second bye is only
+/// given when no one else can take it
+fn find_pairings<F>(players: &[Player], allowed: F) -> Option<Vec<(usize, Option<usize>)>>
+where
+    F: Fn(&Player, &Player) -> bool,
+{
+    let mut paired = vec![false; players.len()];
+    let mut pairs = Vec::new();
+
+    if players.len().is_multiple_of(2) {
+        let mates = perfect_matching(players, &allowed, &paired)?;
+        return pair_remaining(players, &allowed, &mut paired, &mut pairs, &mates).then_some(pairs);
+    }
 
-    // assign bye
-    if let Some(left_over) = left_overs[0].take() {
-        pairings.push(Pairing::new(left_over, None));
+    let mut byes = (0..players.len()).rev().collect::<Vec<usize>>();
+    byes.sort_by_key(|&i| players[i].has_had_bye());
+    for bye in byes {
+        paired[bye] = true;
+        if let Some(mates) = perfect_matching(players, &allowed, &paired)
+            && pair_remaining(players, &allowed, &mut paired, &mut pairs, &mates)
+        {
+            pairs.push((bye, None));
+            return Some(pairs);
+        }
+        paired[bye] = false;
     }
 
-    pairings
+    None
+}
+
+/// `mates` pairs off every unpaired player, a pair is only taken when the players left
+/// after it can still all be paired so the search never has to back out of a dead end
+fn pair_remaining<F>(
+    players: &[Player],
+    allowed: &F,
+    paired: &mut [bool],
+    pairs: &mut Vec<(usize, Option<usize>)>,
+    mates: &[Option<usize>],
+) -> bool
+where
+    F: Fn(&Player, &Player) -> bool,
+{
+    let Some(first) = paired.iter().position(|&p| !p) else {
+        return true;
+    };
+
+    paired[first] = true;
+    for second in first + 1..players.len() {
+        if paired[second] || !allowed(&players[first], &players[second]) {
+            continue;
+        }
+
+        paired[second] = true;
+        let rest = if mates[first] == Some(second) {
+            Some(mates.to_vec())
+        } else {
+            perfect_matching(players, allowed, paired)
+        };
+        if let Some(rest) = rest {
+            pairs.push((first, Some(second)));
+            if pair_remaining(players, allowed, paired, pairs, &rest) {
+                return true;
+            }
+            pairs.pop();
+        }
+        paired[second] = false;
+    }
+    paired[first] = false;
+
+    false
+}
+
+/// every unpaired players mate in a pairing of all of them, if there is one
+fn perfect_matching<F>(players: &[Player], allowed: &F, paired: &[bool]) -> Option<Vec<Option<usize>>>
+where
+    F: Fn(&Player, &Player) -> bool,
+{
+    let free = (0..players.len()).filter(|&i| !paired[i]).collect::<Vec<usize>>();
+    let mut edges = Vec::new();
+    for (a, &i) in free.iter().enumerate() {
+        for (b, &j) in free.iter().enumerate().skip(a + 1) {
+            if allowed(&players[i], &players[j]) {
+                edges.push((a, b, 1));
+            }
+        }
+    }
+
+    let matched = max_weight_matching(&edges, true);
+    if matched.iter().flatten().count() != free.len() {
+        return None;
+    }
+
+    let mut mates = vec![None; players.len()];
+    for (a, mate) in matched.into_iter().enumerate() {
+        mates[free[a]] = mate.map(|b| free[b]);
+    }
+
+    Some(mates)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
-    const SCORES: ScoreConfig = ScoreConfig{
-        win: 3,
-        tie: 1,
-        loss: 0,
-    };
+    const SCORES: ScoreConfig = crate::DEFUALT_SCORING;
 
     fn generate_players(number: u16) -> Vec<Player> {
         (1..number+1).map(|num| Player::new(num.to_string(), num)).collect()
@@ -188,7 +695,7 @@ mod tests {
     fn four_player_all_tie() {
        let mut players = generate_players(4);
 
-       let mut matches = generate_pairings(&mut players, SCORES);
+       let mut matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
        for pair in &mut matches {
            pair.give_outcome(Outcome::Tie);
        }
@@ -203,14 +710,14 @@ mod tests {
 
        assert!(players.iter().all(|p| p.extract_record()==(0,0,1)));
 
-       matches = generate_pairings(&mut players, SCORES);
+       matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
        assert_eq!(2, matches.len());
     }
 
     #[test]
     fn four_players_one_down_pair() {
         let mut players = generate_players(4);
-        let mut matches = generate_pairings(&mut players, SCORES);
+        let mut matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
 
         matches[0].give_outcome(Outcome::Win);
         matches[1].give_outcome(Outcome::Tie);
@@ -223,7 +730,7 @@ mod tests {
        .flatten()
        .collect::<Vec<Player>>();
 
-       matches = generate_pairings(&mut players, SCORES);
+       matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
        let (p1, p2) = matches[0].get_players();
        assert_eq!(p1.extract_record(), (1,0,0));
        assert_eq!(p2.unwrap().extract_record(), (0,0,1));
@@ -234,10 +741,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn two_players_round_2() {
         let mut players = generate_players(2);
-        let mut matches = generate_pairings(&mut players, SCORES);
+        let mut matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
         matches[0].give_outcome(Outcome::Win);
 
         let mut players = matches.into_iter()
@@ -248,7 +754,53 @@ mod tests {
        .flatten()
        .collect::<Vec<Player>>();
 
-       let _matches = generate_pairings(&mut players, SCORES);
+       let error = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng());
+       assert_eq!(error, Err(PairingError::NoLegalPairing));
+       assert_eq!(players.len(), 2);
+
+       let matches = generate_pairings(&mut players, SCORES, RematchPolicy::AllowIfUnavoidable, &mut rand::rng()).unwrap();
+       assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn never_rematch() {
+        let mut players = generate_players(4);
+        for _ in 0..3 {
+            let mut matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
+            for m in &mut matches {
+                m.give_outcome(Outcome::Win);
+            }
+
+            players = matches.into_iter()
+                .flat_map(|e| {
+                    let (p1, p2) = e.extract_players();
+                    [Some(p1), p2]
+                })
+            .flatten()
+            .collect::<Vec<Player>>();
+        }
+
+        // after 3 rounds 4 players have played everyone exactly once
+        for player in &players {
+            let mut opponents = player.get_matches().iter().map(|&(opp, _)| opp).collect::<Vec<u16>>();
+            opponents.sort();
+            opponents.dedup();
+            assert_eq!(opponents.len(), 3);
+        }
+
+        let error = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng());
+        assert_eq!(error, Err(PairingError::NoLegalPairing));
+    }
+
+    #[test]
+    fn odd_bye_goes_to_bottom() {
+        let mut players = generate_players(5);
+        players[0].add_opponent(BYE_PLAYER_NUMBER, Outcome::Win);
+        let matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
+
+        let (bye, none) = matches.last().unwrap().get_players();
+        assert!(none.is_none());
+        assert_eq!(bye.extract_record(), (0, 0, 0));
     }
 
 
@@ -256,7 +808,7 @@ mod tests {
     fn stress_test() {
         let mut players = generate_players(64);
         for _ in 0..12 {
-            let mut matches = generate_pairings(&mut players, SCORES);
+            let mut matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
             for m in &mut matches[1..] {
                 m.give_outcome(Outcome::Win);
             }
@@ -274,6 +826,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn unpairable_players_do_not_hang() {
+        // player 1 has played everyone so only a rematch pairs the round
+        let mut players = generate_players(24);
+        let others = (2..=24).map(|n| (n, Outcome::Win)).collect();
+        players[0] = Player::from_information("1".to_string(), 1, (23, 0, 0), others);
+        for player in &mut players[1..] {
+            player.add_opponent(1, Outcome::Loss);
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        assert_eq!(generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rng).unwrap_err(), PairingError::NoLegalPairing);
+        assert_eq!(players.len(), 24);
+        let pairings = generate_pairings(&mut players, SCORES, RematchPolicy::AllowIfUnavoidable, &mut rng).unwrap();
+        assert_eq!(pairings.len(), 12);
+    }
+
+    #[test]
+    fn first_player_is_white() {
+        let mut players = generate_players(2);
+        let mut matches = generate_pairings(&mut players, SCORES, RematchPolicy::Never, &mut rand::rng()).unwrap();
+        matches[0].give_outcome(Outcome::Win);
+
+        let (p1, p2) = matches.pop().unwrap().extract_players();
+        assert_eq!(p1.get_colours(), &[Colour::White]);
+        assert_eq!(p2.unwrap().get_colours(), &[Colour::Black]);
+    }
+
+    #[test]
+    fn game_scores_decide_matches() {
+        assert_eq!(GameScore::new(2, 1, 0).outcome(), Outcome::Win);
+        assert_eq!(GameScore::new(0, 2, 0).outcome(), Outcome::Loss);
+        assert_eq!(GameScore::new(1, 1, 1).outcome(), Outcome::Tie);
+        assert_eq!(!GameScore::new(2, 1, 0), GameScore::new(1, 2, 0));
+        assert_eq!(GameScore::new(2, 1, 0).to_string(), "2-1");
+        assert_eq!(GameScore::new(1, 1, 1).to_string(), "1-1-1");
+    }
+
     #[test]
     fn not_test() {
         assert_eq!(!Outcome::Win, Outcome::Loss);
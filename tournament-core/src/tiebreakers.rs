@@ -0,0 +1,546 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::player::Player;
+use crate::swiss::{Outcome, Points, ScoreConfig, BYE_PLAYER_NUMBER};
+
+/// No match or game win percentage may go below a third when used as a tiebreaker
+pub const PERCENTAGE_FLOOR: f32 = 1.0 / 3.0;
+
+/// Game points awarded the same way organized play does it, 3 for a win 1 for a draw
+const GAME_WIN_POINTS: f32 = 3.0;
+const GAME_DRAW_POINTS: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tiebreakers {
+    /// OMW%
+    pub opponent_match_win: f32,
+    /// GW%
+    pub game_win: f32,
+    /// OGW%
+    pub opponent_game_win: f32,
+}
+
+/// match points earned divided by the most match points that could have been earned
+/// rounds the player received a bye for still count as played
+pub fn match_win_percentage(player: &Player, scoring: ScoreConfig) -> f32 {
+    let rounds = player.get_matches().len();
+    if rounds == 0 || scoring.win == Points::ZERO {
+        return PERCENTAGE_FLOOR;
+    }
+
+    let possible = rounds as f32 * scoring.win.as_f32();
+    f32::max(player.caluculate_match_points(scoring).as_f32() / possible, PERCENTAGE_FLOOR)
+}
+
+/// game points earned divided by the most game points that could have been earned.
+/// Matches reported without their games are counted as a single game and forfeits
+/// are left out as no games were played
+pub fn game_win_percentage(player: &Player) -> f32 {
+    let (points, games) = player
+        .get_matches()
+        .iter()
+        .zip(player.get_games())
+        .filter(|&(&(_, outcome), _)| !outcome.is_forfeit())
+        .map(|(&(_, outcome), games)| match games {
+            Some(g) => (g.wins as f32 * GAME_WIN_POINTS + g.draws as f32 * GAME_DRAW_POINTS, g.games() as f32),
+            None => match outcome {
+                // a bye is treated as winning every game
+                Outcome::Win | Outcome::Bye => (GAME_WIN_POINTS, 1.0),
+                Outcome::Tie | Outcome::IntentionalDraw => (GAME_DRAW_POINTS, 1.0),
+                Outcome::Loss | Outcome::DoubleLoss => (0.0, 1.0),
+                Outcome::ForfeitWin | Outcome::ForfeitLoss => (0.0, 0.0),
+            },
+        })
+        .fold((0.0, 0.0), |(points, games), (p, g)| (points + p, games + g));
+
+    if games == 0.0 {
+        return PERCENTAGE_FLOOR;
+    }
+
+    f32::max(points / (games * GAME_WIN_POINTS), PERCENTAGE_FLOOR)
+}
+
+/// calculates OMW%, GW% and OGW% for every player keyed by player number
+/// byes and opponents that can not be found in `players` are ignored
+pub fn calculate_tiebreakers<'a, I>(players: I, scoring: ScoreConfig) -> HashMap<u16, Tiebreakers>
+where
+    I: IntoIterator<Item = &'a Player>,
+{
+    let context = TiebreakContext::new(players, scoring);
+
+    context
+        .players
+        .iter()
+        .map(|(&num, player)| {
+            let tiebreakers = Tiebreakers {
+                opponent_match_win: OpponentMatchWin.calculate(player, &context),
+                game_win: GameWin.calculate(player, &context),
+                opponent_game_win: OpponentGameWin.calculate(player, &context),
+            };
+
+            (num, tiebreakers)
+        })
+        .collect()
+}
+
+/// A value used to separate players on the same number of match points,
+/// higher values are always ranked first
+pub trait Tiebreaker {
+    fn name(&self) -> &'static str;
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32;
+}
+
+/// Everything a tiebreaker may need to know about the rest of the field
+pub struct TiebreakContext<'a> {
+    players: HashMap<u16, &'a Player>,
+    scoring: ScoreConfig,
+}
+
+impl<'a> TiebreakContext<'a> {
+    pub fn new<I: IntoIterator<Item = &'a Player>>(players: I, scoring: ScoreConfig) -> Self {
+        Self {
+            players: players.into_iter().map(|p| (p.get_number(), p)).collect(),
+            scoring,
+        }
+    }
+
+    pub fn get_player(&self, number: u16) -> Option<&'a Player> {
+        self.players.get(&number).copied()
+    }
+
+    pub fn scoring(&self) -> ScoreConfig {
+        self.scoring
+    }
+
+    pub fn iter_players(&self) -> impl Iterator<Item = &'a Player> {
+        self.players.values().copied()
+    }
+
+    pub fn match_points(&self, player: &Player) -> f32 {
+        player.caluculate_match_points(self.scoring).as_f32()
+    }
+
+    /// opponents actually faced along with the outcome from `player`s point of view,
+    /// byes and unknown players are skipped
+    pub fn opponents(&self, player: &Player) -> impl Iterator<Item = (&'a Player, Outcome)> {
+        player
+            .get_matches()
+            .iter()
+            .filter(|&&(opp, _)| opp != BYE_PLAYER_NUMBER)
+            .filter_map(|&(opp, outcome)| Some((self.get_player(opp)?, outcome)))
+    }
+
+    fn opponent_scores(&self, player: &Player) -> Vec<f32> {
+        let mut scores = self
+            .opponents(player)
+            .map(|(opp, _)| self.match_points(opp))
+            .collect::<Vec<f32>>();
+        scores.sort_by(f32::total_cmp);
+        scores
+    }
+}
+
+/// OMW%
+pub struct OpponentMatchWin;
+/// GW%
+pub struct GameWin;
+/// OGW%
+pub struct OpponentGameWin;
+/// sum of every opponents score
+pub struct Buchholz;
+/// Buchholz with the lowest opponent removed
+pub struct BuchholzCut1;
+/// Buchholz with the highest and lowest opponents removed
+pub struct MedianBuchholz;
+/// sum of beaten opponents scores plus a share of drawn opponents scores
+pub struct SonnebornBerger;
+/// sum of the running score after each round
+pub struct ProgressiveScore;
+/// points scored against opponents on the same score
+pub struct DirectEncounter;
+
+impl Tiebreaker for OpponentMatchWin {
+    fn name(&self) -> &'static str {
+        "OMW%"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        average(context.opponents(player).map(|(opp, _)| match_win_percentage(opp, context.scoring)))
+    }
+}
+
+impl Tiebreaker for GameWin {
+    fn name(&self) -> &'static str {
+        "GW%"
+    }
+
+    fn calculate(&self, player: &Player, _context: &TiebreakContext) -> f32 {
+        game_win_percentage(player)
+    }
+}
+
+impl Tiebreaker for OpponentGameWin {
+    fn name(&self) -> &'static str {
+        "OGW%"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        average(context.opponents(player).map(|(opp, _)| game_win_percentage(opp)))
+    }
+}
+
+impl Tiebreaker for Buchholz {
+    fn name(&self) -> &'static str {
+        "Buchholz"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        context.opponent_scores(player).iter().sum()
+    }
+}
+
+impl Tiebreaker for BuchholzCut1 {
+    fn name(&self) -> &'static str {
+        "Buchholz Cut 1"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        context.opponent_scores(player).iter().skip(1).sum()
+    }
+}
+
+impl Tiebreaker for MedianBuchholz {
+    fn name(&self) -> &'static str {
+        "Median Buchholz"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        let scores = context.opponent_scores(player);
+        if scores.len() < 3 {
+            return scores.iter().sum();
+        }
+
+        scores[1..scores.len() - 1].iter().sum()
+    }
+}
+
+impl Tiebreaker for SonnebornBerger {
+    fn name(&self) -> &'static str {
+        "Sonneborn-Berger"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        let scoring = context.scoring;
+        if scoring.win == Points::ZERO {
+            return 0.0;
+        }
+
+        context
+            .opponents(player)
+            .map(|(opp, outcome)| {
+                let share = scoring.points_for(outcome).as_f32() / scoring.win.as_f32();
+                context.match_points(opp) * share
+            })
+            .sum()
+    }
+}
+
+impl Tiebreaker for ProgressiveScore {
+    fn name(&self) -> &'static str {
+        "Progressive Score"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        player
+            .get_matches()
+            .iter()
+            .scan(0.0, |running, &(_, outcome)| {
+                *running += context.scoring.points_for(outcome).as_f32();
+                Some(*running)
+            })
+            .sum()
+    }
+}
+
+impl Tiebreaker for DirectEncounter {
+    fn name(&self) -> &'static str {
+        "Direct Encounter"
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        let own_points = context.match_points(player);
+        context
+            .opponents(player)
+            .filter(|(opp, _)| context.match_points(opp) == own_points)
+            .map(|(_, outcome)| context.scoring.points_for(outcome).as_f32())
+            .sum()
+    }
+}
+
+/// Every tiebreaker that can be put into a tournaments chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum TiebreakerKind {
+    OpponentMatchWin,
+    GameWin,
+    OpponentGameWin,
+    Buchholz,
+    BuchholzCut1,
+    MedianBuchholz,
+    SonnebornBerger,
+    ProgressiveScore,
+    DirectEncounter,
+}
+
+impl TiebreakerKind {
+    fn tiebreaker(self) -> &'static dyn Tiebreaker {
+        match self {
+            Self::OpponentMatchWin => &OpponentMatchWin,
+            Self::GameWin => &GameWin,
+            Self::OpponentGameWin => &OpponentGameWin,
+            Self::Buchholz => &Buchholz,
+            Self::BuchholzCut1 => &BuchholzCut1,
+            Self::MedianBuchholz => &MedianBuchholz,
+            Self::SonnebornBerger => &SonnebornBerger,
+            Self::ProgressiveScore => &ProgressiveScore,
+            Self::DirectEncounter => &DirectEncounter,
+        }
+    }
+}
+
+impl TryFrom<u8> for TiebreakerKind {
+    type Error = u8;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::OpponentMatchWin,
+            1 => Self::GameWin,
+            2 => Self::OpponentGameWin,
+            3 => Self::Buchholz,
+            4 => Self::BuchholzCut1,
+            5 => Self::MedianBuchholz,
+            6 => Self::SonnebornBerger,
+            7 => Self::ProgressiveScore,
+            8 => Self::DirectEncounter,
+            e => return Err(e),
+        })
+    }
+}
+
+impl Tiebreaker for TiebreakerKind {
+    fn name(&self) -> &'static str {
+        self.tiebreaker().name()
+    }
+
+    fn calculate(&self, player: &Player, context: &TiebreakContext) -> f32 {
+        self.tiebreaker().calculate(player, context)
+    }
+}
+
+/// what organized play for trading card games uses
+pub const TCG_TIEBREAKERS: [TiebreakerKind; 3] = [
+    TiebreakerKind::OpponentMatchWin,
+    TiebreakerKind::GameWin,
+    TiebreakerKind::OpponentGameWin,
+];
+
+/// a common chain for chess and go events
+pub const CHESS_TIEBREAKERS: [TiebreakerKind; 4] = [
+    TiebreakerKind::BuchholzCut1,
+    TiebreakerKind::Buchholz,
+    TiebreakerKind::SonnebornBerger,
+    TiebreakerKind::ProgressiveScore,
+];
+
+/// values of every tiebreaker in `chain` for every player, in chain order
+pub fn evaluate_chain<T: Tiebreaker>(chain: &[T], context: &TiebreakContext) -> HashMap<u16, Vec<f32>> {
+    context
+        .players
+        .iter()
+        .map(|(&num, player)| {
+            (num, chain.iter().map(|t| t.calculate(player, context)).collect())
+        })
+        .collect()
+}
+
+/// sorts players by match points then each tiebreaker in turn, best first
+/// players who can not be separated are left in player number order
+pub fn rank_players<'a, T: Tiebreaker>(chain: &[T], context: &TiebreakContext<'a>) -> Vec<&'a Player> {
+    let values = evaluate_chain(chain, context);
+    let mut players = context.players.values().copied().collect::<Vec<&Player>>();
+
+    players.sort_by(|a, b| {
+        context
+            .match_points(b)
+            .total_cmp(&context.match_points(a))
+            .then_with(|| compare_values(&values[&b.get_number()], &values[&a.get_number()]))
+            .then_with(|| a.get_number().cmp(&b.get_number()))
+    });
+
+    players
+}
+
+pub(crate) fn compare_values(a: &[f32], b: &[f32]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| a.total_cmp(b))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn average<I: Iterator<Item = f32>>(values: I) -> f32 {
+    let (count, sum) = values.fold((0, 0.0), |(count, sum), v| (count + 1, sum + v));
+    if count == 0 {
+        return 0.0;
+    }
+
+    sum / count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFUALT_SCORING;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.0001
+    }
+
+    #[test]
+    fn floor_applied() {
+        let player = Player::from_information("1".to_string(), 1, (0, 3, 0), vec![
+            (2, Outcome::Loss),
+            (3, Outcome::Loss),
+            (4, Outcome::Loss),
+        ]);
+
+        assert!(close(match_win_percentage(&player, DEFUALT_SCORING), PERCENTAGE_FLOOR));
+        assert!(close(game_win_percentage(&player), PERCENTAGE_FLOOR));
+    }
+
+    #[test]
+    fn byes_not_counted_as_opponents() {
+        let players = vec![
+            Player::from_information("1".to_string(), 1, (2, 0, 0), vec![(2, Outcome::Win), (BYE_PLAYER_NUMBER, Outcome::Win)]),
+            Player::from_information("2".to_string(), 2, (1, 1, 0), vec![(1, Outcome::Loss), (3, Outcome::Win)]),
+            Player::from_information("3".to_string(), 3, (1, 1, 0), vec![(BYE_PLAYER_NUMBER, Outcome::Win), (2, Outcome::Loss)]),
+        ];
+
+        let tiebreakers = calculate_tiebreakers(&players, DEFUALT_SCORING);
+
+        // only opponent was player 2 who is 1-1
+        assert!(close(tiebreakers[&1].opponent_match_win, 0.5));
+        assert!(close(tiebreakers[&1].game_win, 1.0));
+        assert!(close(tiebreakers[&1].opponent_game_win, 0.5));
+
+        // 1 is 2-0 and 3 is 1-1
+        assert!(close(tiebreakers[&2].opponent_match_win, 0.75));
+        assert!(close(tiebreakers[&3].opponent_match_win, 0.5));
+    }
+
+    #[test]
+    fn ties_worth_a_third() {
+        let players = vec![
+            Player::from_information("1".to_string(), 1, (1, 0, 1), vec![(2, Outcome::Tie), (3, Outcome::Win)]),
+            Player::from_information("2".to_string(), 2, (0, 0, 1), vec![(1, Outcome::Tie)]),
+            Player::from_information("3".to_string(), 3, (0, 1, 0), vec![(1, Outcome::Loss)]),
+        ];
+
+        let tiebreakers = calculate_tiebreakers(&players, DEFUALT_SCORING);
+
+        assert!(close(tiebreakers[&1].game_win, 4.0 / 6.0));
+        // (1/3 + 1/3) / 2 both floored
+        assert!(close(tiebreakers[&1].opponent_match_win, PERCENTAGE_FLOOR));
+        assert!(close(tiebreakers[&2].opponent_match_win, 4.0 / 6.0));
+    }
+
+    fn chess_field() -> Vec<Player> {
+        // 1 beat 2 and drew 3, 2 beat 4, 3 drew 1 and beat 4, 4 lost both
+        vec![
+            Player::from_information("1".to_string(), 1, (1, 0, 1), vec![(2, Outcome::Win), (3, Outcome::Tie)]),
+            Player::from_information("2".to_string(), 2, (1, 1, 0), vec![(1, Outcome::Loss), (4, Outcome::Win)]),
+            Player::from_information("3".to_string(), 3, (1, 0, 1), vec![(4, Outcome::Win), (1, Outcome::Tie)]),
+            Player::from_information("4".to_string(), 4, (0, 2, 0), vec![(3, Outcome::Loss), (2, Outcome::Loss)]),
+        ]
+    }
+
+    const CHESS: ScoreConfig = ScoreConfig {
+        win: Points::new(2),
+        tie: Points::new(1),
+        ..crate::CHESS_SCORING
+    };
+
+    #[test]
+    fn buchholz_variants() {
+        let players = chess_field();
+        let context = TiebreakContext::new(&players, CHESS);
+        let p1 = &players[0];
+
+        // opponents 2 and 3 have 2 and 3 points
+        assert_eq!(Buchholz.calculate(p1, &context), 5.0);
+        assert_eq!(BuchholzCut1.calculate(p1, &context), 3.0);
+        assert_eq!(MedianBuchholz.calculate(p1, &context), 5.0);
+    }
+
+    #[test]
+    fn sonneborn_berger_and_progressive() {
+        let players = chess_field();
+        let context = TiebreakContext::new(&players, CHESS);
+
+        // beat 2 (2 points) and drew 3 (3 points)
+        assert_eq!(SonnebornBerger.calculate(&players[0], &context), 3.5);
+        // 2 then 3
+        assert_eq!(ProgressiveScore.calculate(&players[0], &context), 5.0);
+        // 0 then 2
+        assert_eq!(ProgressiveScore.calculate(&players[1], &context), 2.0);
+    }
+
+    #[test]
+    fn direct_encounter_only_counts_equal_scores() {
+        let players = chess_field();
+        let context = TiebreakContext::new(&players, CHESS);
+
+        assert_eq!(DirectEncounter.calculate(&players[0], &context), 1.0);
+        assert_eq!(DirectEncounter.calculate(&players[1], &context), 0.0);
+    }
+
+    struct HighestNumber;
+
+    impl Tiebreaker for HighestNumber {
+        fn name(&self) -> &'static str {
+            "Highest Number"
+        }
+
+        fn calculate(&self, player: &Player, _context: &TiebreakContext) -> f32 {
+            player.get_number() as f32
+        }
+    }
+
+    #[test]
+    fn chain_ranks_in_order() {
+        let players = chess_field();
+        let context = TiebreakContext::new(&players, CHESS);
+
+        let ranked = rank_players(&[HighestNumber], &context)
+            .iter()
+            .map(|p| p.get_number())
+            .collect::<Vec<u16>>();
+        assert_eq!(ranked, vec![3, 1, 2, 4]);
+
+        // 1 and 3 drew so direct encounter can not split them but Buchholz can
+        let ranked = rank_players(&[TiebreakerKind::DirectEncounter, TiebreakerKind::Buchholz], &context)
+            .iter()
+            .map(|p| p.get_number())
+            .collect::<Vec<u16>>();
+        assert_eq!(ranked, vec![1, 3, 2, 4]);
+    }
+
+    #[test]
+    fn no_matches_played() {
+        let players = vec![Player::new("1".to_string(), 1)];
+        let tiebreakers = calculate_tiebreakers(&players, DEFUALT_SCORING);
+        assert_eq!(tiebreakers[&1].opponent_match_win, 0.0);
+        assert_eq!(tiebreakers[&1].opponent_game_win, 0.0);
+    }
+}
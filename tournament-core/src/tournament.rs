@@ -1,20 +1,107 @@
+use std::collections::HashMap;
 use std::fmt::{Debug, Display};
 use std::fs::File;
 use std::path::Path;
 use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::error::Error;
 
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 
-use crate::swiss::{generate_pairings, Outcome};
+use crate::audit::{ByeSelection, ByeSkip, Float, FloatReason, PairingAudit, PairingTrace, ScoreBracket};
+use crate::backup::Autosave;
+use crate::elimination::{Bracket, BracketError, BracketMatch, DoubleElimination, SingleElimination};
+use crate::engine::{fewest_tag_clashes, PairingContext, PairingEngine, PairingSystem};
+use crate::journal::{Event, Journal};
+use crate::round_robin::Seating;
+use crate::standings::{calculate_standings, Standing};
+use crate::swiss::{ByeValue, Colour, GameScore, Outcome, PairingError, Points, RematchPolicy, ScoreConfig, TagPolicy, BYE_PLAYER_NUMBER};
+use crate::tiebreakers::{calculate_tiebreakers, rank_players, TiebreakContext, TiebreakerKind, Tiebreakers, TCG_TIEBREAKERS};
 use crate::{player::Player, swiss::Pairing};
-use crate::DEFUALT_SCORING;
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tournament {
     round_number: u16,
     players: Vec<Player>,
     pairings: Vec<Pairing>,
+    /// players who have left, they keep their record and count towards standings
+    /// and tiebreakers but are never paired
+    dropped: Vec<Player>,
     name: String,
+    /// tiebreakers applied in order after match points
+    tiebreakers: Vec<TiebreakerKind>,
+    scoring: ScoreConfig,
+    rematch_policy: RematchPolicy,
+    pairing_system: PairingSystem,
+    /// where a round robin seated its players, see [`PairingEngine::seat`]
+    seating: Option<Seating>,
+    /// every round is paired from this so the same seed and results give the same pairings
+    seed: u64,
+    /// once started every round is paired from the bracket instead of the pairing system
+    top_cut: Option<Bracket>,
+    tag_policy: TagPolicy,
+    /// see [`Tournament::get_relaxed_tags`]
+    relaxed_tags: Vec<(u16, u16)>,
+    /// when set every round paired adds to `audits`
+    auditing: bool,
+    /// how each round was paired, saved with the tournament
+    audits: Vec<PairingAudit>,
+    /// every change made since the tournament was created or loaded
+    #[cfg_attr(feature = "serde", serde(skip))]
+    journal: Journal,
+    /// saves the tournament after every change when set
+    #[cfg_attr(feature = "serde", serde(skip))]
+    autosave: Option<Autosave>,
+}
+
+/// tournaments are equal when everything saved to file is, how they got there, where they
+/// save to and the tags relaxed for the last round paired are left out
+impl PartialEq for Tournament {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            round_number,
+            players,
+            pairings,
+            dropped,
+            name,
+            tiebreakers,
+            scoring,
+            rematch_policy,
+            pairing_system,
+            seating,
+            seed,
+            top_cut,
+            tag_policy,
+            relaxed_tags: _,
+            auditing,
+            audits,
+            journal: _,
+            autosave: _,
+        } = self;
+
+        *round_number == other.round_number
+            && *players == other.players
+            && *pairings == other.pairings
+            && *dropped == other.dropped
+            && *name == other.name
+            && *tiebreakers == other.tiebreakers
+            && *scoring == other.scoring
+            && *rematch_policy == other.rematch_policy
+            && *pairing_system == other.pairing_system
+            && *seating == other.seating
+            && *seed == other.seed
+            && *top_cut == other.top_cut
+            && *tag_policy == other.tag_policy
+            && *auditing == other.auditing
+            && *audits == other.audits
+    }
+}
+
+impl Default for Tournament {
+    fn default() -> Self {
+        Self::new(String::new(), Vec::new())
+    }
 }
 
 impl Tournament {
@@ -23,13 +110,39 @@ impl Tournament {
             round_number: 0,
             players,
             pairings: Vec::new(),
+            dropped: Vec::new(),
             name,
+            tiebreakers: TCG_TIEBREAKERS.to_vec(),
+            scoring: ScoreConfig::default(),
+            rematch_policy: RematchPolicy::default(),
+            pairing_system: PairingSystem::default(),
+            seating: None,
+            seed: rand::random(),
+            top_cut: None,
+            tag_policy: TagPolicy::default(),
+            relaxed_tags: Vec::new(),
+            auditing: false,
+            audits: Vec::new(),
+            journal: Journal::default(),
+            autosave: None,
+        }
+    }
+
+    pub fn with_scoring(name: String, players: Vec<Player>, scoring: ScoreConfig) -> Self {
+        Self {
+            scoring,
+            ..Self::new(name, players)
         }
     }
 
-    pub fn add_player(&mut self, mut player: Player) {
+    pub fn add_player(&mut self, player: Player) {
+        // adding a player can not fail
+        let _ = self.apply(Event::AddPlayer(player));
+    }
+
+    fn insert_player(&mut self, mut player: Player) {
         if self.round_number > 0 {
-            (0..self.round_number).for_each(|_| player.add_opponent(0, Outcome::Loss));
+            (0..self.round_number).for_each(|_| player.add_opponent(BYE_PLAYER_NUMBER, Outcome::Loss));
         }
 
         self.players.push(player);
@@ -39,6 +152,74 @@ impl Tournament {
         &self.players
     }
 
+    pub fn get_dropped_players(&self) -> &[Player] {
+        &self.dropped
+    }
+
+    pub fn is_dropped(&self, number: u16) -> bool {
+        self.dropped.iter().any(|p| p.get_number() == number)
+    }
+
+    /// stops a player from being paired in future rounds. During a round with nothing reported
+    /// yet it is paired again without them, a bracket can not lose a player once seeded
+    pub fn drop_player(&mut self, number: u16) -> Result<(), TournamentError> {
+        self.apply(Event::DropPlayer(number))
+    }
+
+    fn remove_player(&mut self, number: u16) -> Result<(), TournamentError> {
+        if self.top_cut.is_some() {
+            return Err(TournamentError::TopCutAlreadyStarted);
+        }
+
+        if !self.pairings.is_empty() {
+            self.check_unreported()?;
+            return self.pair_again(|t| t.remove_player(number));
+        }
+
+        let idx = self
+            .players
+            .iter()
+            .position(|p| p.get_number() == number)
+            .ok_or(TournamentError::PlayerNotFound(number))?;
+        self.dropped.push(self.players.remove(idx));
+
+        Ok(())
+    }
+
+    /// brings a dropped player back, the rounds they missed count as losses like a late entry.
+    /// Like dropping this pairs a round with nothing reported yet again
+    pub fn undrop_player(&mut self, number: u16) -> Result<(), TournamentError> {
+        self.apply(Event::UndropPlayer(number))
+    }
+
+    fn restore_player(&mut self, number: u16) -> Result<(), TournamentError> {
+        if self.top_cut.is_some() {
+            return Err(TournamentError::TopCutAlreadyStarted);
+        }
+
+        if !self.pairings.is_empty() {
+            self.check_unreported()?;
+            return self.pair_again(|t| t.restore_player(number));
+        }
+
+        let idx = self
+            .dropped
+            .iter()
+            .position(|p| p.get_number() == number)
+            .ok_or(TournamentError::PlayerNotFound(number))?;
+        let mut player = self.dropped.remove(idx);
+        let missed = self.round_number as usize - player.get_matches().len();
+        (0..missed).for_each(|_| player.add_opponent(BYE_PLAYER_NUMBER, Outcome::Loss));
+        self.players.push(player);
+
+        Ok(())
+    }
+
+    /// swiss rounds finished so far
+    pub fn get_round_number(&self) -> u16 {
+        self.round_number
+    }
+
     pub fn get_pairings(&self) -> &[Pairing] {
         &self.pairings
     }
@@ -47,35 +228,589 @@ impl Tournament {
         &self.pairings[match_index]
     }
 
+    /// every player in the tournament including those currently sat in a pairing
+    /// and those who have dropped
+    pub fn iter_all_players(&self) -> impl Iterator<Item = &Player> {
+        self.players.iter().chain(self.dropped.iter()).chain(
+            self.pairings
+                .iter()
+                .flat_map(|p| {
+                    let (p1, p2) = p.get_players();
+                    [Some(p1), p2]
+                })
+                .flatten()
+        )
+    }
+
+    /// OMW%, GW% and OGW% keyed by player number
+    pub fn calculate_tiebreakers(&self) -> HashMap<u16, Tiebreakers> {
+        calculate_tiebreakers(self.iter_all_players(), self.scoring)
+    }
+
+    pub fn get_scoring(&self) -> ScoreConfig {
+        self.scoring
+    }
+
+    pub fn set_scoring(&mut self, scoring: ScoreConfig) {
+        let _ = self.apply(Event::SetScoring(scoring));
+    }
+
+    pub fn get_rematch_policy(&self) -> RematchPolicy {
+        self.rematch_policy
+    }
+
+    pub fn set_rematch_policy(&mut self, rematch_policy: RematchPolicy) {
+        let _ = self.apply(Event::SetRematchPolicy(rematch_policy));
+    }
+
+    pub fn get_pairing_system(&self) -> PairingSystem {
+        self.pairing_system
+    }
+
+    pub fn set_pairing_system(&mut self, pairing_system: PairingSystem) {
+        let _ = self.apply(Event::SetPairingSystem(pairing_system));
+    }
+
+    /// the seats of a round robin once its first round is paired
+    pub fn get_seating(&self) -> Option<&Seating> {
+        self.seating.as_ref()
+    }
+
+    /// the seed the pairings are generated from, shown so a round can be audited
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        let _ = self.apply(Event::SetSeed(seed));
+    }
+
+    /// each round gets its own stream of the seed so replaying one round does not
+    /// depend on how much randomness the earlier rounds used
+    fn round_rng(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(self.round_number as u64);
+        rng
+    }
+
+    fn pairing_context(&self) -> PairingContext<'static> {
+        PairingContext {
+            scoring: self.scoring,
+            rematches: self.rematch_policy,
+            separate_tags: self.tag_policy.separates(self.round_number),
+            tag_exceptions: &[],
+            round: self.round_number,
+            seating: None,
+        }
+    }
+
+    pub fn get_tag_policy(&self) -> TagPolicy {
+        self.tag_policy
+    }
+
+    pub fn set_tag_policy(&mut self, tag_policy: TagPolicy) {
+        let _ = self.apply(Event::SetTagPolicy(tag_policy));
+    }
+
+    /// changes the tag of a player who is not in a pairing
+    pub fn set_player_tag(&mut self, number: u16, tag: Option<String>) -> Result<(), TournamentError> {
+        self.apply(Event::SetPlayerTag(number, tag))
+    }
+
+    /// pairs sharing a tag who were only paired this round because it could not be paired
+    /// otherwise, empty when every tag constraint was kept
+    pub fn get_relaxed_tags(&self) -> &[(u16, u16)] {
+        &self.relaxed_tags
+    }
+
+    pub fn get_tiebreakers(&self) -> &[TiebreakerKind] {
+        &self.tiebreakers
+    }
+
+    pub fn set_tiebreakers(&mut self, tiebreakers: Vec<TiebreakerKind>) {
+        let _ = self.apply(Event::SetTiebreakers(tiebreakers));
+    }
+
+    /// every player sorted by match points then the tiebreaker chain, best first
+    pub fn ranked_players(&self) -> Vec<&Player> {
+        let context = TiebreakContext::new(self.iter_all_players(), self.scoring);
+        rank_players(&self.tiebreakers, &context)
+    }
+
+    /// ranked standings table using this tournaments scoring and tiebreakers
+    pub fn standings(&self) -> Vec<Standing> {
+        let context = TiebreakContext::new(self.iter_all_players(), self.scoring);
+        calculate_standings(&self.tiebreakers, &context)
+    }
+
+    pub fn get_top_cut(&self) -> Option<&Bracket> {
+        self.top_cut.as_ref()
+    }
+
+    /// player numbers of everyone still in the event, best in the standings first
+    fn seeded_players(&self) -> Vec<u16> {
+        self.standings()
+            .into_iter()
+            .map(|s| s.player_number)
+            .filter(|&n| !self.is_dropped(n))
+            .collect()
+    }
+
+    /// ends the swiss rounds and seeds the best `size` players still in the event into
+    /// a single elimination bracket, following rounds are paired from it
+    pub fn start_top_cut(&mut self, size: usize) -> Result<(), TournamentError> {
+        self.apply(Event::StartTopCut(size))
+    }
+
+    fn seed_top_cut(&mut self, size: usize) -> Result<(), TournamentError> {
+        if !self.pairings.is_empty() {
+            return Err(TournamentError::RoundAlreadyStarted);
+        }
+
+        if self.top_cut.is_some() {
+            return Err(TournamentError::TopCutAlreadyStarted);
+        }
+
+        let mut seeds = self.seeded_players();
+        if seeds.len() < size {
+            return Err(BracketError::InvalidSize(size).into());
+        }
+        seeds.truncate(size);
+
+        self.top_cut = Some(Bracket::Single(SingleElimination::new(seeds)?));
+
+        Ok(())
+    }
+
+    /// seeds every player still in the event into a double elimination bracket, this
+    /// can be done before the first round to skip swiss entirely. With `bracket_reset`
+    /// the grand final is played again if the losers bracket champion wins it
+    pub fn start_double_elimination(&mut self, bracket_reset: bool) -> Result<(), TournamentError> {
+        self.apply(Event::StartDoubleElimination(bracket_reset))
+    }
+
+    fn seed_double_elimination(&mut self, bracket_reset: bool) -> Result<(), TournamentError> {
+        if !self.pairings.is_empty() {
+            return Err(TournamentError::RoundAlreadyStarted);
+        }
+
+        if self.top_cut.is_some() {
+            return Err(TournamentError::TopCutAlreadyStarted);
+        }
+
+        let bracket = DoubleElimination::new(self.seeded_players(), bracket_reset)?;
+        self.top_cut = Some(Bracket::Double(bracket));
+
+        Ok(())
+    }
+
+    /// pairs the next round of the bracket, players out of it sit in `players`
+    fn start_top_cut_round(&mut self) -> Result<(), TournamentError> {
+        let Some(top_cut) = &mut self.top_cut else {
+            return Ok(());
+        };
+
+        let matches = top_cut.next_round()?.to_vec();
+        let mut seated = matches.iter().flat_map(|m| [m.top, m.bottom]);
+        if let Some(missing) = seated.find(|&n| !self.players.iter().any(|p| p.get_number() == n)) {
+            return Err(TournamentError::PlayerNotFound(missing));
+        }
+
+        for BracketMatch { top, bottom, .. } in matches {
+            let mut take = |number: u16| {
+                let idx = self.players.iter().position(|p| p.get_number() == number).unwrap();
+                self.players.remove(idx)
+            };
+            let top = take(top);
+            let bottom = take(bottom);
+            self.pairings.push(Pairing::new(top, Some(bottom)));
+        }
+
+        Ok(())
+    }
+
     pub fn start_round(&mut self) -> Result<(), TournamentError> {
-        if self.players.is_empty() {
+        self.apply(Event::StartRound)
+    }
+
+    fn pair_round(&mut self) -> Result<(), TournamentError> {
+        if self.players.is_empty() || !self.pairings.is_empty() {
             return Err(TournamentError::RoundAlreadyStarted);
         }
 
-        self.pairings = generate_pairings(&mut self.players, DEFUALT_SCORING);
+        if self.top_cut.is_some() {
+            return self.start_top_cut_round();
+        }
+
+        // a round robin keeps the seats it started with so a drop never reshuffles it
+        let kept = self.seating.take();
+        let seating = kept.clone().or_else(|| self.pairing_system.seat(&self.players, self.round_number));
+        let result = self.pair_field(seating.as_ref());
+        self.seating = if result.is_ok() { seating } else { kept };
+
+        result
+    }
+
+    fn pair_field(&mut self, seating: Option<&Seating>) -> Result<(), TournamentError> {
+        self.relaxed_tags.clear();
+        let exceptions;
+        let mut context = PairingContext {
+            seating,
+            ..self.pairing_context()
+        };
+        let (failed_byes, trace) = match self.pair_players(context) {
+            // soft tag constraints give way rather than leave the round unpaired, only as
+            // few as have to at first and all of them if the engine still can not pair
+            Err(TournamentError::Pairing(_)) if context.separate_tags && self.tag_policy != TagPolicy::Never => {
+                exceptions = fewest_tag_clashes(&self.players, &context);
+                context.tag_exceptions = &exceptions;
+                let paired = match self.pair_players(context) {
+                    Err(TournamentError::Pairing(_)) => {
+                        context.separate_tags = false;
+                        self.pair_players(context)?
+                    },
+                    result => result?,
+                };
+                self.relaxed_tags = self
+                    .pairings
+                    .iter()
+                    .filter_map(|p| match p.get_players() {
+                        (p1, Some(p2)) if p1.shares_tag(p2) => Some((p1.get_number(), p2.get_number())),
+                        _ => None,
+                    })
+                    .collect();
+                paired
+            },
+            result => result?,
+        };
+
+        if self.auditing {
+            self.audit_round(trace, &failed_byes);
+        }
 
         Ok(())
     }
 
-    pub fn report_match(&mut self, match_idx: usize, outcome: Outcome) -> Result<(), TournamentError> {
+    /// pairs the round handing back the players who could not be given the bye and what
+    /// the engine recorded as it paired
+    fn pair_players(&mut self, context: PairingContext) -> Result<(Vec<u16>, PairingTrace), TournamentError> {
+        let mut rng = self.round_rng();
+        if self.players.len().is_multiple_of(2) || self.pairing_system.picks_bye() {
+            let mut trace = PairingTrace::default();
+            self.pairings = self.pairing_system.pair(&mut self.players, &context, &mut rng, &mut trace)?;
+            // the engine tried its own bye candidates
+            let failed = std::mem::take(&mut trace.byes_tried);
+            return Ok((failed, trace));
+        }
+
+        let mut failed = Vec::new();
+        for number in self.bye_candidates() {
+            let idx = self.players.iter().position(|p| p.get_number() == number).unwrap();
+            let bye = self.players.remove(idx);
+            let mut trace = PairingTrace::default();
+            match self.pairing_system.pair(&mut self.players, &context, &mut rng, &mut trace) {
+                Ok(pairings) => {
+                    self.pairings = pairings;
+                    self.pairings.push(Pairing::new(bye, None));
+                    return Ok((failed, trace));
+                },
+                Err(_) => {
+                    self.players.insert(idx, bye);
+                    failed.push(number);
+                },
+            }
+        }
+
+        Err(PairingError::NoLegalPairing.into())
+    }
+
+    fn audit_round(&mut self, trace: PairingTrace, failed_byes: &[u16]) {
+        let mut audit = PairingAudit {
+            round: self.round_number + 1,
+            seed: self.seed,
+            system: self.pairing_system,
+            relaxed_tags: self.relaxed_tags.clone(),
+            ..PairingAudit::default()
+        };
+        audit.read_trace(trace, &self.pairings);
+        audit.read_bye(&self.pairings, &self.ranked_players(), failed_byes);
+
+        // pairing a round again replaces what was recorded for it
+        self.audits.retain(|a| a.round != audit.round);
+        self.audits.push(audit);
+    }
+
+    /// turns recording how every round is paired on or off, see [`Tournament::get_audits`]
+    pub fn set_auditing(&mut self, auditing: bool) {
+        let _ = self.apply(Event::SetAuditing(auditing));
+    }
+
+    pub fn is_auditing(&self) -> bool {
+        self.auditing
+    }
+
+    /// how every round paired while auditing was turned on, saved to file with the rest
+    pub fn get_audits(&self) -> &[PairingAudit] {
+        &self.audits
+    }
+
+    /// every audit as text, for exporting
+    pub fn audit_report(&self) -> String {
+        self.audits.iter().map(|a| a.to_string()).collect::<Vec<String>>().join("\n")
+    }
+
+    /// throws the current pairings away and pairs the round again, for when a player was
+    /// added or dropped by mistake after it started. Results already reported would be
+    /// lost so this fails if there are any unless `discard_results` is set
+    pub fn repair_round(&mut self, discard_results: bool) -> Result<(), TournamentError> {
+        self.apply(Event::RepairRound(discard_results))
+    }
+
+    fn unpair_round(&mut self, discard_results: bool) -> Result<(), TournamentError> {
         if self.pairings.is_empty() {
             return Err(TournamentError::RoundNotImprogress);
         }
 
+        if !discard_results {
+            self.check_unreported()?;
+        }
+
+        self.pair_again(|_| Ok(()))
+    }
+
+    fn check_unreported(&self) -> Result<(), TournamentError> {
+        if self.pairings.iter().any(|p| p.is_delcared() && !p.is_bye()) {
+            return Err(TournamentError::ResultsReported);
+        }
+
+        Ok(())
+    }
+
+    /// takes everyone out of the round in progress, makes `change` to them and pairs the
+    /// round again. Nothing changes if either fails
+    fn pair_again(&mut self, change: impl FnOnce(&mut Self) -> Result<(), TournamentError>) -> Result<(), TournamentError> {
+        let pool = self.players.clone();
+        let dropped = self.dropped.clone();
+        let previous = std::mem::take(&mut self.pairings);
+        for pairing in previous.iter().cloned() {
+            let (p1, p2) = pairing.into_players();
+            self.players.push(p1);
+            self.players.extend(p2);
+        }
+
+        // sorted so the same players always pair the same way
+        let result = change(self).and_then(|()| {
+            self.players.sort_by_key(|p| p.get_number());
+            self.pair_round()
+        });
+        if let Err(e) = result {
+            self.players = pool;
+            self.dropped = dropped;
+            self.pairings = previous;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// swaps the players sat in two seats, a seat being the match index and the colour
+    /// of the side. Results reported for either match are cleared
+    pub fn swap_seats(&mut self, a: (usize, Colour), b: (usize, Colour)) -> Result<(), TournamentError> {
+        self.apply(Event::SwapSeats(a, b))
+    }
+
+    fn exchange_seats(&mut self, a: (usize, Colour), b: (usize, Colour)) -> Result<(), TournamentError> {
+        self.check_manual_change()?;
+        for (match_idx, colour) in [a, b] {
+            let pairing = self.pairings.get(match_idx).ok_or(TournamentError::InvalidMatchIndex(match_idx))?;
+            if pairing.get_seat(colour).is_none() {
+                return Err(TournamentError::MatchIsBye(match_idx));
+            }
+        }
+
+        if a.0 == b.0 {
+            if a.1 != b.1 {
+                self.pairings[a.0].swap_sides();
+                self.pairings[a.0].clear_result();
+            }
+            return Ok(());
+        }
+
+        let [first, second] = self.pairings.get_disjoint_mut([a.0, b.0]).unwrap();
+        std::mem::swap(first.get_seat_mut(a.1).unwrap(), second.get_seat_mut(b.1).unwrap());
+        first.clear_result();
+        second.clear_result();
+
+        Ok(())
+    }
+
+    /// pairs two players who are not in a pairing by hand, without a second player it is a bye
+    pub fn create_pairing(&mut self, first: u16, second: Option<u16>) -> Result<(), TournamentError> {
+        self.apply(Event::CreatePairing(first, second))
+    }
+
+    fn insert_pairing(&mut self, first: u16, second: Option<u16>) -> Result<(), TournamentError> {
+        self.check_manual_change()?;
+        let find = |players: &[Player], number: u16| {
+            players
+                .iter()
+                .position(|p| p.get_number() == number)
+                .ok_or(TournamentError::PlayerNotFound(number))
+        };
+
+        let first_idx = find(&self.players, first)?;
+        let first = self.players.remove(first_idx);
+        let second = match second.map(|n| find(&self.players, n)) {
+            Some(Ok(idx)) => Some(self.players.remove(idx)),
+            Some(Err(e)) => {
+                self.players.insert(first_idx, first);
+                return Err(e);
+            },
+            None => None,
+        };
+        self.pairings.push(Pairing::new(first, second));
+
+        Ok(())
+    }
+
+    /// breaks up a pairing sending its players back to be paired again
+    pub fn delete_pairing(&mut self, match_idx: usize) -> Result<(), TournamentError> {
+        self.apply(Event::DeletePairing(match_idx))
+    }
+
+    fn remove_pairing(&mut self, match_idx: usize) -> Result<(), TournamentError> {
+        self.check_manual_change()?;
         if match_idx >= self.pairings.len() {
             return Err(TournamentError::InvalidMatchIndex(match_idx));
         }
 
+        let (p1, p2) = self.pairings.remove(match_idx).into_players();
+        self.players.push(p1);
+        self.players.extend(p2);
+
+        Ok(())
+    }
+
+    /// elimination matches come from the bracket so they can not be changed by hand
+    fn check_manual_change(&self) -> Result<(), TournamentError> {
+        if self.top_cut.is_some() {
+            return Err(TournamentError::TopCutAlreadyStarted);
+        }
+
+        Ok(())
+    }
+
+    /// checks the current pairings, every active player has to be in exactly one of them
+    /// for the round to finish while rematches are only worth a warning
+    pub fn validate_pairings(&self) -> PairingCheck {
+        let mut check = PairingCheck::default();
+        if self.pairings.is_empty() || self.top_cut.is_some() {
+            return check;
+        }
+
+        check.unpaired = self.players.iter().map(|p| p.get_number()).collect();
+        check.rematches = self
+            .pairings
+            .iter()
+            .filter_map(|p| match p.get_players() {
+                (p1, Some(p2)) if p1.has_played(p2.get_number()) => Some((p1.get_number(), p2.get_number())),
+                _ => None,
+            })
+            .collect();
+
+        check
+    }
+
+    /// player numbers that could take the bye, lowest in the standings first.
+    /// Players who already had one come after everyone who has not, as a last resort
+    fn bye_candidates(&self) -> Vec<u16> {
+        let ranked = self.ranked_players();
+        let (fresh, repeats): (Vec<&Player>, Vec<&Player>) = ranked
+            .into_iter()
+            .rev()
+            .filter(|p| !self.is_dropped(p.get_number()))
+            .partition(|p| !p.has_had_bye());
+
+        fresh.into_iter().chain(repeats).map(|p| p.get_number()).collect()
+    }
+
+    pub fn report_match(&mut self, match_idx: usize, outcome: Outcome) -> Result<(), TournamentError> {
+        self.apply(Event::ReportMatch(match_idx, outcome))
+    }
+
+    fn give_outcome(&mut self, match_idx: usize, outcome: Outcome) -> Result<(), TournamentError> {
+        self.check_report(match_idx, outcome)?;
         self.pairings[match_idx].give_outcome(outcome);
 
         Ok(())
     }
 
+    /// reports every game of a match from the side of the first player, the match
+    /// outcome is worked out from them
+    pub fn report_games(&mut self, match_idx: usize, games: GameScore) -> Result<(), TournamentError> {
+        self.apply(Event::ReportGames(match_idx, games))
+    }
+
+    fn give_games(&mut self, match_idx: usize, games: GameScore) -> Result<(), TournamentError> {
+        self.check_report(match_idx, games.outcome())?;
+        self.pairings[match_idx].give_games(games);
+
+        Ok(())
+    }
+
+    fn check_report(&self, match_idx: usize, outcome: Outcome) -> Result<(), TournamentError> {
+        if self.pairings.is_empty() {
+            return Err(TournamentError::RoundNotImprogress);
+        }
+
+        if match_idx >= self.pairings.len() {
+            return Err(TournamentError::InvalidMatchIndex(match_idx));
+        }
+
+        if self.pairings[match_idx].is_bye() {
+            return Err(TournamentError::MatchIsBye(match_idx));
+        }
+
+        if self.top_cut.is_some() && !outcome.has_winner() {
+            return Err(TournamentError::TieInElimination(match_idx));
+        }
+
+        Ok(())
+    }
+
     pub fn finilze_round(&mut self) -> Result<(), TournamentError> {
+        self.apply(Event::FinalizeRound)
+    }
+
+    fn close_round(&mut self) -> Result<(), TournamentError> {
         if !self.pairings.iter().all(|p| p.is_delcared()) {
             return Err(TournamentError::GamesNotFinished);
         }
 
+        let unpaired = self.validate_pairings().unpaired;
+        if !unpaired.is_empty() {
+            return Err(TournamentError::PlayersUnpaired(unpaired));
+        }
+
+        // bracket results stay in the bracket so the swiss records are left alone
+        if let Some(top_cut) = &mut self.top_cut {
+            for pairing in &self.pairings {
+                let winner = match pairing.get_outcome() {
+                    Some(Outcome::Loss | Outcome::ForfeitLoss) => pairing.get_players().1.unwrap().get_number(),
+                    _ => pairing.get_players().0.get_number(),
+                };
+                top_cut.report_winner(winner)?;
+            }
+
+            for pairing in self.pairings.drain(..) {
+                let (p1, p2) = pairing.into_players();
+                self.players.push(p1);
+                self.players.extend(p2);
+            }
+
+            return Ok(());
+        }
+
         self.players = self.pairings
             .drain(..)
             .flat_map(|e| {
@@ -84,118 +819,985 @@ impl Tournament {
             })
             .flatten()
             .collect::<Vec<Player>>();
+        self.round_number += 1;
+        self.relaxed_tags.clear();
+
+        Ok(())
+    }
+
+    pub fn get_journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    /// makes a change to the tournament and records it so it can be undone,
+    /// every other method that changes the tournament goes through here
+    pub fn apply(&mut self, event: Event) -> Result<(), TournamentError> {
+        if self.journal.get_start().is_none() {
+            self.journal.set_start(self.clone());
+        }
+
+        self.perform(event.clone())?;
+        self.journal.record(event);
+        self.save_now();
+
+        Ok(())
+    }
+
+    fn perform(&mut self, event: Event) -> Result<(), TournamentError> {
+        match event {
+            Event::AddPlayer(player) => self.insert_player(player),
+            Event::DropPlayer(number) => self.remove_player(number)?,
+            Event::UndropPlayer(number) => self.restore_player(number)?,
+            Event::StartRound => self.pair_round()?,
+            Event::RepairRound(discard_results) => self.unpair_round(discard_results)?,
+            Event::SwapSeats(a, b) => self.exchange_seats(a, b)?,
+            Event::CreatePairing(first, second) => self.insert_pairing(first, second)?,
+            Event::DeletePairing(match_idx) => self.remove_pairing(match_idx)?,
+            Event::ReportMatch(match_idx, outcome) => self.give_outcome(match_idx, outcome)?,
+            Event::ReportGames(match_idx, games) => self.give_games(match_idx, games)?,
+            Event::FinalizeRound => self.close_round()?,
+            Event::StartTopCut(size) => self.seed_top_cut(size)?,
+            Event::StartDoubleElimination(bracket_reset) => self.seed_double_elimination(bracket_reset)?,
+            Event::SetScoring(scoring) => self.scoring = scoring,
+            Event::SetRematchPolicy(rematch_policy) => self.rematch_policy = rematch_policy,
+            Event::SetPairingSystem(pairing_system) => {
+                self.pairing_system = pairing_system;
+                self.seating = None;
+            },
+            Event::SetSeed(seed) => self.seed = seed,
+            Event::SetTiebreakers(tiebreakers) => self.tiebreakers = tiebreakers,
+            Event::SetTagPolicy(tag_policy) => self.tag_policy = tag_policy,
+            Event::SetAuditing(auditing) => self.auditing = auditing,
+            Event::SetPlayerTag(number, tag) => self
+                .players
+                .iter_mut()
+                .chain(self.dropped.iter_mut())
+                .find(|p| p.get_number() == number)
+                .ok_or(TournamentError::PlayerNotFound(number))?
+                .set_tag(tag),
+        }
+
+        Ok(())
+    }
+
+    /// rolls back the last change
+    pub fn undo(&mut self) -> Result<(), TournamentError> {
+        let count = self.journal.get_events().len();
+        if count == 0 {
+            return Err(TournamentError::NothingToUndo);
+        }
+
+        self.undo_to(count - 1)
+    }
+
+    /// rolls back to how the tournament was after its first `count` events by replaying
+    /// them from the start, everything after can still be redone
+    pub fn undo_to(&mut self, count: usize) -> Result<(), TournamentError> {
+        let mut journal = self.journal.clone();
+        journal.rewind(count);
+        let Some(start) = journal.get_start() else {
+            return Ok(());
+        };
+
+        let mut tournament = start.clone();
+        for event in journal.get_events() {
+            tournament.perform(event.clone())?;
+        }
+        tournament.journal = journal;
+        tournament.autosave = std::mem::take(&mut self.autosave);
+        *self = tournament;
+        self.save_now();
+
+        Ok(())
+    }
+
+    /// applies the last undone change again
+    pub fn redo(&mut self) -> Result<(), TournamentError> {
+        let event = self.journal.get_undone().last().cloned().ok_or(TournamentError::NothingToRedo)?;
+        self.perform(event)?;
+        self.journal.forward();
+        self.save_now();
+
+        Ok(())
+    }
+
+    /// saves the tournament straight away and then after every change, `None` stops saving
+    pub fn set_autosave(&mut self, autosave: Option<Autosave>) {
+        self.autosave = autosave;
+        self.save_now();
+    }
+
+    pub fn get_autosave(&self) -> Option<&Autosave> {
+        self.autosave.as_ref()
+    }
+
+    fn save_now(&mut self) {
+        if let Some(mut autosave) = self.autosave.take() {
+            autosave.save(self);
+            self.autosave = Some(autosave);
+        }
+    }
+
+    /// writes to a temporary file next to `out_file` that replaces it once it is on disk,
+    /// a failed save leaves whatever was there before
+    pub fn write_to_file<P: AsRef<Path>>(&self, out_file: P) -> std::io::Result<()>{
+        let out_file = out_file.as_ref();
+        let mut temp_file = out_file.as_os_str().to_owned();
+        temp_file.push(".tmp");
+
+        let result = File::create(&temp_file).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            self.write_to(&mut writer)?;
+            let file = writer.into_inner().map_err(|e| e.into_error())?;
+            file.sync_all()?;
+            std::fs::rename(&temp_file, out_file)
+        });
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_file);
+        }
+        result?;
+
+        // the rename is only kept through a crash once the directory is synced too. The new
+        // save is already in place so a filesystem that can not sync directories is no error
+        #[cfg(unix)]
+        {
+            let directory = match out_file.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let _ = File::open(directory).and_then(|d| d.sync_all());
+        }
+
+        Ok(())
+    }
+
+    /// writes the newest version of the .sts layout
+    pub fn write_to<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+
+        writer.write_all(self.name.as_bytes())?;
+
+        
+        writer.write_all(b"\n")?;
+        writer.write_all(&self.round_number.to_le_bytes())?;
+        writer.write_all(&(self.saved_players().count() as u16).to_le_bytes())?;
+        // write out players
+        for player in self.saved_players() {
+            writer.write_all(player.get_name().as_bytes())?;
+            writer.write_all(b"\n")?;
+            writer.write_all(&player.get_number().to_le_bytes())?;
+            // write protection?
+            write_matches(&mut writer, &player.get_matches()[..self.round_number as usize])?;
+        }
+
+        // colours came after the first layout so they follow every player
+        for player in self.saved_players() {
+            write_colours(&mut writer, player)?;
+        }
+        writer.write_all(&self.seed.to_le_bytes())?;
+
+        // dropped players can have missed rounds so each one carries its own count
+        writer.write_all(&(self.dropped.len() as u16).to_le_bytes())?;
+        for player in &self.dropped {
+            writer.write_all(player.get_name().as_bytes())?;
+            writer.write_all(b"\n")?;
+            writer.write_all(&player.get_number().to_le_bytes())?;
+            writer.write_all(&(player.get_matches().len() as u16).to_le_bytes())?;
+            write_matches(&mut writer, player.get_matches())?;
+            write_colours(&mut writer, player)?;
+        }
+
+        match &self.top_cut {
+            None => writer.write_all(&[0])?,
+            Some(Bracket::Single(top_cut)) => {
+                writer.write_all(&[1])?;
+                write_seeds(&mut writer, top_cut.get_seeds())?;
+
+                writer.write_all(&(top_cut.get_rounds().len() as u16).to_le_bytes())?;
+                for round in top_cut.get_rounds() {
+                    writer.write_all(&(round.len() as u16).to_le_bytes())?;
+                    for m in round {
+                        writer.write_all(&m.top.to_le_bytes())?;
+                        writer.write_all(&m.bottom.to_le_bytes())?;
+                        // no player uses the bye number so it marks an undecided match
+                        writer.write_all(&m.winner.unwrap_or(BYE_PLAYER_NUMBER).to_le_bytes())?;
+                    }
+                }
+            },
+            // the rest of the bracket is rebuilt by replaying the results
+            Some(Bracket::Double(bracket)) => {
+                writer.write_all(&[2])?;
+                write_seeds(&mut writer, bracket.get_seeds())?;
+                writer.write_all(&[bracket.has_bracket_reset() as u8])?;
+                write_seeds(&mut writer, bracket.get_results())?;
+            },
+        }
+
+        // a flag for every match saying if its games follow
+        let active = self.saved_players().map(|p| &p.get_games()[..self.round_number as usize]);
+        for games in active.chain(self.dropped.iter().map(|p| p.get_games())) {
+            for game in games {
+                match game {
+                    None => writer.write_all(&[0])?,
+                    Some(g) => writer.write_all(&[1, g.wins, g.losses, g.draws])?,
+                }
+            }
+        }
+
+        // scoring in half points, the bye as a kind followed by its points when custom
+        for points in [self.scoring.win, self.scoring.loss, self.scoring.tie, self.scoring.double_loss] {
+            writer.write_all(&points.halves().to_le_bytes())?;
+        }
+        let (bye_kind, bye_points) = match self.scoring.bye {
+            ByeValue::Win => (0_u8, Points::ZERO),
+            ByeValue::Tie => (1, Points::ZERO),
+            ByeValue::Points(points) => (2, points),
+        };
+        writer.write_all(&[bye_kind])?;
+        writer.write_all(&bye_points.halves().to_le_bytes())?;
+
+        // the tag policy then a line for the tag of every player, empty for none
+        let (tag_kind, tag_round) = match self.tag_policy {
+            TagPolicy::Ignore => (0_u8, 0_u16),
+            TagPolicy::Never => (1, 0),
+            TagPolicy::AvoidUntil(round) => (2, round),
+        };
+        writer.write_all(&[tag_kind])?;
+        writer.write_all(&tag_round.to_le_bytes())?;
+        for player in self.saved_players().chain(self.dropped.iter()) {
+            writer.write_all(player.get_tag().unwrap_or_default().as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+
+        // the round in progress, its players are the last ones saved
+        writer.write_all(&(self.pairings.len() as u16).to_le_bytes())?;
+        for pairing in &self.pairings {
+            let (p1, p2) = pairing.get_players();
+            writer.write_all(&p1.get_number().to_le_bytes())?;
+            writer.write_all(&p2.map_or(BYE_PLAYER_NUMBER, |p| p.get_number()).to_le_bytes())?;
+            match (pairing.get_games(), pairing.get_outcome()) {
+                (Some(g), _) => writer.write_all(&[2, g.wins, g.losses, g.draws])?,
+                (None, Some(outcome)) if !pairing.is_bye() => writer.write_all(&[1, outcome as u8])?,
+                _ => writer.write_all(&[0])?,
+            }
+        }
+
+        // how rounds are paired and ranked, a byte each with the tiebreakers in order
+        writer.write_all(&[self.pairing_system as u8])?;
+        writer.write_all(&[match self.rematch_policy {
+            RematchPolicy::Never => 0,
+            RematchPolicy::AllowIfUnavoidable => 1,
+        }])?;
+        writer.write_all(&[self.tiebreakers.len() as u8])?;
+        for &tiebreaker in &self.tiebreakers {
+            writer.write_all(&[tiebreaker as u8])?;
+        }
+
+        // then the seats of a round robin behind a flag, the round it started and each pod
+        writer.write_all(&[self.seating.is_some() as u8])?;
+        if let Some(seating) = &self.seating {
+            writer.write_all(&seating.get_first_round().to_le_bytes())?;
+            writer.write_all(&(seating.get_pods().len() as u16).to_le_bytes())?;
+            for pod in seating.get_pods() {
+                write_seeds(&mut writer, pod)?;
+            }
+        }
+
+        // and last whether rounds are audited followed by every audit kept so far
+        writer.write_all(&[self.auditing as u8])?;
+        writer.write_all(&(self.audits.len() as u16).to_le_bytes())?;
+        for audit in &self.audits {
+            write_audit(&mut writer, audit)?;
+        }
+
+        Ok(())
+    }
+
+    /// everything the .sts layout holds, and the pairing audits, as JSON for scripts
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    pub fn read_from_file<P: AsRef<Path>>(in_file: P) -> Result<Self, TournamentIOError> {
+        let file = File::options().read(true).open(in_file)?;
+        Self::read_from(BufReader::new(file))
+    }
+
+    /// reads any version of the .sts layout, files from before it was versioned included.
+    /// Files are checked as they are read so a damaged one gives an error and never a panic
+    pub fn read_from<R: BufRead>(reader: R) -> Result<Self, TournamentIOError> {
+        let mut reader = OffsetReader::new(reader);
+        Self::read_checked(&mut reader).map_err(|e| match e {
+            TournamentIOError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                TournamentIOError::UnexpectedEnd(reader.offset())
+            },
+            e => e,
+        })
+    }
+
+    fn read_checked<R: BufRead>(reader: &mut OffsetReader<R>) -> Result<Self, TournamentIOError> {
+        let version = if reader.fill_buf()?.starts_with(MAGIC) {
+            reader.consume(MAGIC.len());
+            read_u16(reader)?
+        } else {
+            UNVERSIONED
+        };
+
+        if version > FORMAT_VERSION {
+            return Err(TournamentIOError::UnsupportedVersion(version));
+        }
+
+        let tournament = Self::read_version(reader, version)?;
+        if !reader.fill_buf()?.is_empty() {
+            return Err(TournamentIOError::TrailingData(reader.offset()));
+        }
+
+        Ok(tournament)
+    }
+
+    /// every version is read here, sections are read or left at their default depending on
+    /// which version added them so older files are migrated to the newest layout as they load
+    fn read_version<R: BufRead>(reader: &mut OffsetReader<R>, version: u16) -> Result<Self, TournamentIOError> {
+        if reader.fill_buf()?.is_empty() {
+            return Err(TournamentIOError::EmptyFile);
+        }
+        let name = read_name(reader)?;
+
+        let mut round_number = [0_u8; 2];
+        turn_eof_into_discriptive(reader.read_exact(&mut round_number), TournamentIOError::MissingRoundNumber)?;
+        let round_number = u16::from_le_bytes(round_number);
+        
+        let mut number_of_players = [0_u8; 2];
+        turn_eof_into_discriptive(reader.read_exact(&mut number_of_players), TournamentIOError::MissingPlayerNumber)?;
+        let number_of_players = u16::from_le_bytes(number_of_players);
+
+        // where the matches of each player start, so they can be checked against their opponents
+        let mut match_offsets = HashMap::new();
+        let mut players: Vec<Player> = Vec::new();
+        for _ in 0..number_of_players {
+            let player_name = read_name(reader)?;
+            let player_number = read_player_number(reader, &match_offsets)?;
+            players.push(read_player_matches(reader, player_name, player_number, round_number, &mut match_offsets)?);
+        }
+
+        let mut tournament = Self {
+            round_number,
+            players,
+            ..Self::new(name, Vec::new())
+        };
+
+        // files saved before colours were tracked end here
+        if has_section(reader, version)? {
+            for player in &mut tournament.players {
+                read_colours(reader, player)?;
+            }
+        }
+
+        // and files saved before the seed was kept end here, they get a fresh one
+        if has_section(reader, version)? {
+            let mut seed = [0_u8; 8];
+            reader.read_exact(&mut seed)?;
+            tournament.seed = u64::from_le_bytes(seed);
+        }
+
+        // then those saved before players could drop
+        if has_section(reader, version)? {
+            for _ in 0..read_u16(reader)? {
+                let player_name = read_name(reader)?;
+                let player_number = read_player_number(reader, &match_offsets)?;
+
+                let offset = reader.offset();
+                let played = read_u16(reader)?;
+                if played > round_number {
+                    return Err(TournamentIOError::PlayerHasTooManyRounds(offset, round_number, played));
+                }
+
+                let mut player = read_player_matches(reader, player_name, player_number, played, &mut match_offsets)?;
+                read_colours(reader, &mut player)?;
+                tournament.dropped.push(player);
+            }
+        }
+
+        // and those saved before top cuts
+        if has_section(reader, version)? {
+            let offset = reader.offset();
+            let mut bracket_kind = [0_u8];
+            reader.read_exact(&mut bracket_kind)?;
+            match bracket_kind[0] {
+                0 => {},
+                1 => {
+                    let seeds = read_bracket_seeds(reader, &match_offsets)?;
+
+                    let offset = reader.offset();
+                    let mut rounds = Vec::new();
+                    for _ in 0..read_u16(reader)? {
+                        let mut round = Vec::new();
+                        for _ in 0..read_u16(reader)? {
+                            let mut m = BracketMatch::new(read_u16(reader)?, read_u16(reader)?);
+                            m.winner = Some(read_u16(reader)?).filter(|&w| w != BYE_PLAYER_NUMBER);
+                            round.push(m);
+                        }
+                        rounds.push(round);
+                    }
+
+                    let top_cut = SingleElimination::from_rounds(seeds, rounds)
+                        .map_err(|e| TournamentIOError::InvalidBracket(offset, e))?;
+                    tournament.top_cut = Some(Bracket::Single(top_cut));
+                },
+                2 => {
+                    let seeds = read_bracket_seeds(reader, &match_offsets)?;
+                    let bracket_reset = read_flag(reader)?;
+                    let offset = reader.offset();
+                    let results = read_seeds(reader)?;
+
+                    let bracket = DoubleElimination::from_results(seeds, bracket_reset, &results)
+                        .map_err(|e| TournamentIOError::InvalidBracket(offset, e))?;
+                    tournament.top_cut = Some(Bracket::Double(bracket));
+                },
+                e => return Err(TournamentIOError::InvalidBracketKind(offset, e)),
+            }
+        }
+
+        // and those saved before game scores
+        if has_section(reader, version)? {
+            for player in tournament.players.iter_mut().chain(tournament.dropped.iter_mut()) {
+                for idx in 0..player.get_matches().len() {
+                    if read_flag(reader)? {
+                        let mut score = [0_u8; 3];
+                        reader.read_exact(&mut score)?;
+                        player.set_games(idx, Some(GameScore::new(score[0], score[1], score[2])));
+                    }
+                }
+            }
+        }
+
+        // and those saved before the scoring was kept, they use the default
+        if has_section(reader, version)? {
+            let win = Points::from_halves(read_u32(reader)?);
+            let loss = Points::from_halves(read_u32(reader)?);
+            let tie = Points::from_halves(read_u32(reader)?);
+            let double_loss = Points::from_halves(read_u32(reader)?);
+
+            let offset = reader.offset();
+            let mut bye_kind = [0_u8];
+            reader.read_exact(&mut bye_kind)?;
+            let bye_points = Points::from_halves(read_u32(reader)?);
+            let bye = match bye_kind[0] {
+                0 => ByeValue::Win,
+                1 => ByeValue::Tie,
+                2 => ByeValue::Points(bye_points),
+                e => return Err(TournamentIOError::InvalidByeKind(offset, e)),
+            };
+
+            tournament.scoring = ScoreConfig {
+                win,
+                loss,
+                tie,
+                bye,
+                double_loss,
+            };
+        }
+
+        // and those saved before players could be tagged
+        if has_section(reader, version)? {
+            let offset = reader.offset();
+            let mut tag_kind = [0_u8];
+            reader.read_exact(&mut tag_kind)?;
+            let tag_round = read_u16(reader)?;
+            tournament.tag_policy = match tag_kind[0] {
+                0 => TagPolicy::Ignore,
+                1 => TagPolicy::Never,
+                2 => TagPolicy::AvoidUntil(tag_round),
+                e => return Err(TournamentIOError::InvalidTagPolicy(offset, e)),
+            };
+
+            for player in tournament.players.iter_mut().chain(tournament.dropped.iter_mut()) {
+                let tag = read_name(reader)?;
+                player.set_tag((!tag.is_empty()).then_some(tag));
+            }
+        }
+
+        tournament.check_results(&match_offsets)?;
+
+        if version >= 2 {
+            tournament.read_pairings(reader)?;
+        }
+
+        // files from before this use the default pairing and tiebreakers
+        if version >= 3 {
+            tournament.read_settings(reader, &match_offsets)?;
+        }
+
+        Ok(tournament)
+    }
+
+    /// both players of a match save it, they have to agree on who played who and how it went
+    fn check_results(&self, match_offsets: &HashMap<u16, usize>) -> Result<(), TournamentIOError> {
+        let everyone = self
+            .players
+            .iter()
+            .chain(self.dropped.iter())
+            .map(|p| (p.get_number(), p))
+            .collect::<HashMap<u16, &Player>>();
+
+        for player in self.players.iter().chain(self.dropped.iter()) {
+            let number = player.get_number();
+            let matches = player.get_matches().iter().zip(player.get_games()).enumerate();
+            for (round, (&(opponent, outcome), &games)) in matches {
+                // each match is a u16 opponent and a u8 outcome
+                let offset = match_offsets[&number] + round * 3;
+                if opponent == BYE_PLAYER_NUMBER {
+                    continue;
+                }
+                if opponent == number {
+                    return Err(TournamentIOError::PlayedThemselves(offset, number));
+                }
+
+                let other = everyone.get(&opponent).ok_or(TournamentIOError::UnknownPlayer(offset, opponent))?;
+                let agrees = other.get_matches().get(round) == Some(&(number, !outcome))
+                    && other.get_games().get(round) == Some(&games.map(|g| !g));
+                if !agrees {
+                    return Err(TournamentIOError::AsymmetricResult(offset, number, opponent));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// active players in the order they are saved, those sat in a pairing come last
+    fn saved_players(&self) -> impl Iterator<Item = &Player> {
+        self.players.iter().chain(
+            self.pairings
+                .iter()
+                .flat_map(|p| {
+                    let (p1, p2) = p.get_players();
+                    [Some(p1), p2]
+                })
+                .flatten()
+        )
+    }
+
+    /// seats the players of the round in progress, they were read in with everyone else
+    fn read_pairings<R: BufRead>(&mut self, reader: &mut OffsetReader<R>) -> Result<(), TournamentIOError> {
+        let mut pairings = Vec::new();
+        for _ in 0..read_u16(reader)? {
+            let mut take = |reader: &mut OffsetReader<R>| {
+                let offset = reader.offset();
+                let number = read_u16(reader)?;
+                if number == BYE_PLAYER_NUMBER {
+                    return Ok(None);
+                }
+
+                let idx = self
+                    .players
+                    .iter()
+                    .position(|p| p.get_number() == number)
+                    .ok_or(TournamentIOError::UnknownPlayer(offset, number))?;
+                Ok::<Option<Player>, TournamentIOError>(Some(self.players.remove(idx)))
+            };
+
+            let offset = reader.offset();
+            let p1 = take(reader)?.ok_or(TournamentIOError::UnknownPlayer(offset, BYE_PLAYER_NUMBER))?;
+            let p2 = take(reader)?;
+            let mut pairing = Pairing::new(p1, p2);
+
+            let offset = reader.offset();
+            let mut state = [0_u8];
+            reader.read_exact(&mut state)?;
+            match state[0] {
+                0 => {},
+                1 => {
+                    let offset = reader.offset();
+                    let mut outcome = [0_u8];
+                    reader.read_exact(&mut outcome)?;
+                    let outcome = Outcome::try_from(outcome[0]).map_err(|e| TournamentIOError::InvalidResultFound(offset, e))?;
+                    pairing.give_outcome(outcome);
+                },
+                2 => {
+                    let mut score = [0_u8; 3];
+                    reader.read_exact(&mut score)?;
+                    pairing.give_games(GameScore::new(score[0], score[1], score[2]));
+                },
+                e => return Err(TournamentIOError::InvalidFlag(offset, e)),
+            }
+            pairings.push(pairing);
+        }
+        self.pairings = pairings;
+
+        Ok(())
+    }
+
+    fn read_settings<R: BufRead>(&mut self, reader: &mut OffsetReader<R>, players: &HashMap<u16, usize>) -> Result<(), TournamentIOError> {
+        let mut byte = [0_u8];
+        let mut read_byte = |reader: &mut OffsetReader<R>| {
+            let offset = reader.offset();
+            reader.read_exact(&mut byte)?;
+            Ok::<(usize, u8), std::io::Error>((offset, byte[0]))
+        };
+
+        let (offset, system) = read_byte(reader)?;
+        self.pairing_system = PairingSystem::try_from(system).map_err(|e| TournamentIOError::InvalidPairingSystem(offset, e))?;
+
+        let (offset, rematches) = read_byte(reader)?;
+        self.rematch_policy = match rematches {
+            0 => RematchPolicy::Never,
+            1 => RematchPolicy::AllowIfUnavoidable,
+            e => return Err(TournamentIOError::InvalidRematchPolicy(offset, e)),
+        };
+
+        let (_, count) = read_byte(reader)?;
+        self.tiebreakers = (0..count)
+            .map(|_| {
+                let (offset, kind) = read_byte(reader)?;
+                TiebreakerKind::try_from(kind).map_err(|e| TournamentIOError::InvalidTiebreaker(offset, e))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if read_flag(reader)? {
+            let first_round = read_u16(reader)?;
+            let pods = (0..read_u16(reader)?)
+                .map(|_| read_bracket_seeds(reader, players))
+                .collect::<Result<_, _>>()?;
+            self.seating = Some(Seating::new(first_round, pods));
+        }
+
+        self.auditing = read_flag(reader)?;
+        self.audits = (0..read_u16(reader)?).map(|_| read_audit(reader)).collect::<Result<_, _>>()?;
+
+        Ok(())
+    }
+}
+
+/// starts every versioned file, no unversioned file starts with it as names never hold a nul
+const MAGIC: &[u8; 4] = b"STS\0";
+/// the headerless layout, sections added to it over time are only there when the file goes on
+const UNVERSIONED: u16 = 0;
+/// bumped whenever the layout changes
+pub const FORMAT_VERSION: u16 = 3;
+
+/// every section of the unversioned layout is always written from the first version on
+fn has_section<R: BufRead>(reader: &mut R, version: u16) -> std::io::Result<bool> {
+    Ok(version > UNVERSIONED || !reader.fill_buf()?.is_empty())
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> std::io::Result<u16> {
+    let mut bytes = [0_u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0_u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn write_seeds<W: Write>(writer: &mut W, numbers: &[u16]) -> std::io::Result<()> {
+    writer.write_all(&(numbers.len() as u16).to_le_bytes())?;
+    for number in numbers {
+        writer.write_all(&number.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn read_seeds<R: Read>(reader: &mut R) -> std::io::Result<Vec<u16>> {
+    (0..read_u16(reader)?).map(|_| read_u16(reader)).collect()
+}
+
+fn write_pairs<W: Write>(writer: &mut W, pairs: &[(u16, u16)]) -> std::io::Result<()> {
+    writer.write_all(&(pairs.len() as u16).to_le_bytes())?;
+    for (a, b) in pairs {
+        writer.write_all(&a.to_le_bytes())?;
+        writer.write_all(&b.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn read_pairs<R: Read>(reader: &mut R) -> std::io::Result<Vec<(u16, u16)>> {
+    (0..read_u16(reader)?).map(|_| Ok((read_u16(reader)?, read_u16(reader)?))).collect()
+}
+
+/// points in half points and players for each bracket, then the floats with their reason as
+/// a byte, the pairs noted and the bye behind a flag
+fn write_audit<W: Write>(writer: &mut W, audit: &PairingAudit) -> std::io::Result<()> {
+    writer.write_all(&audit.round.to_le_bytes())?;
+    writer.write_all(&audit.seed.to_le_bytes())?;
+    writer.write_all(&[audit.system as u8])?;
+
+    writer.write_all(&(audit.brackets.len() as u16).to_le_bytes())?;
+    for bracket in &audit.brackets {
+        writer.write_all(&bracket.points.halves().to_le_bytes())?;
+        write_seeds(writer, &bracket.players)?;
+    }
+
+    writer.write_all(&(audit.floats.len() as u16).to_le_bytes())?;
+    for float in &audit.floats {
+        writer.write_all(&float.player.to_le_bytes())?;
+        writer.write_all(&float.opponent.to_le_bytes())?;
+        writer.write_all(&float.from.halves().to_le_bytes())?;
+        writer.write_all(&float.to.halves().to_le_bytes())?;
+        writer.write_all(&[float.reason as u8])?;
+    }
+
+    write_pairs(writer, &audit.rematches_avoided)?;
+    write_pairs(writer, &audit.rematches)?;
+    write_pairs(writer, &audit.relaxed_tags)?;
+
+    writer.write_all(&[audit.bye.is_some() as u8])?;
+    if let Some(bye) = &audit.bye {
+        writer.write_all(&bye.player.to_le_bytes())?;
+        writer.write_all(&[bye.chosen_by_engine as u8])?;
+        writer.write_all(&(bye.passed_over.len() as u16).to_le_bytes())?;
+        for &(number, skip) in &bye.passed_over {
+            writer.write_all(&number.to_le_bytes())?;
+            writer.write_all(&[skip as u8])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// audits only explain past rounds so the players they name are not checked
+fn read_audit<R: BufRead>(reader: &mut OffsetReader<R>) -> Result<PairingAudit, TournamentIOError> {
+    let mut byte = [0_u8];
+    let mut read_byte = |reader: &mut OffsetReader<R>| {
+        let offset = reader.offset();
+        reader.read_exact(&mut byte)?;
+        Ok::<(usize, u8), std::io::Error>((offset, byte[0]))
+    };
+
+    let round = read_u16(reader)?;
+    let mut seed = [0_u8; 8];
+    reader.read_exact(&mut seed)?;
+    let (offset, system) = read_byte(reader)?;
+    let system = PairingSystem::try_from(system).map_err(|e| TournamentIOError::InvalidPairingSystem(offset, e))?;
+
+    let mut brackets = Vec::new();
+    for _ in 0..read_u16(reader)? {
+        let points = Points::from_halves(read_u32(reader)?);
+        brackets.push(ScoreBracket { points, players: read_seeds(reader)? });
+    }
+
+    let mut floats = Vec::new();
+    for _ in 0..read_u16(reader)? {
+        let player = read_u16(reader)?;
+        let opponent = read_u16(reader)?;
+        let from = Points::from_halves(read_u32(reader)?);
+        let to = Points::from_halves(read_u32(reader)?);
+        let (offset, reason) = read_byte(reader)?;
+        let reason = FloatReason::try_from(reason).map_err(|e| TournamentIOError::InvalidFloatReason(offset, e))?;
+        floats.push(Float { player, opponent, from, to, reason });
+    }
+
+    let rematches_avoided = read_pairs(reader)?;
+    let rematches = read_pairs(reader)?;
+    let relaxed_tags = read_pairs(reader)?;
+
+    let bye = if read_flag(reader)? {
+        let player = read_u16(reader)?;
+        let chosen_by_engine = read_flag(reader)?;
+        let mut passed_over = Vec::new();
+        for _ in 0..read_u16(reader)? {
+            let number = read_u16(reader)?;
+            let (offset, skip) = read_byte(reader)?;
+            let skip = ByeSkip::try_from(skip).map_err(|e| TournamentIOError::InvalidByeSkip(offset, e))?;
+            passed_over.push((number, skip));
+        }
+        Some(ByeSelection { player, chosen_by_engine, passed_over })
+    } else {
+        None
+    };
+
+    Ok(PairingAudit {
+        round,
+        seed: u64::from_le_bytes(seed),
+        system,
+        brackets,
+        floats,
+        rematches_avoided,
+        rematches,
+        bye,
+        relaxed_tags,
+    })
+}
+
+fn write_matches<W: Write>(writer: &mut W, matches: &[(u16, Outcome)]) -> std::io::Result<()> {
+    for &(opp_num, outcome) in matches {
+        writer.write_all(&opp_num.to_le_bytes())?;
+        writer.write_all(&[outcome as u8])?;
+    }
+
+    Ok(())
+}
+
+fn write_colours<W: Write>(writer: &mut W, player: &Player) -> std::io::Result<()> {
+    writer.write_all(&(player.get_colours().len() as u16).to_le_bytes())?;
+    for &colour in player.get_colours() {
+        writer.write_all(&[colour as u8])?;
+    }
+
+    Ok(())
+}
+
+/// reads up to the next newline, which is dropped
+fn read_name<R: BufRead>(reader: &mut OffsetReader<R>) -> Result<String, TournamentIOError> {
+    let start = reader.offset();
+    let mut bytes = Vec::new();
+    reader.read_until(b'\n', &mut bytes)?;
+    if bytes.pop() != Some(b'\n') {
+        return Err(TournamentIOError::MissingNewLineSeperator(reader.offset()));
+    }
+
+    String::from_utf8(bytes).map_err(|e| TournamentIOError::InvalidUtf8(start + e.utf8_error().valid_up_to()))
+}
+
+/// `seen` holds the numbers of every player read so far
+fn read_player_number<R: BufRead>(reader: &mut OffsetReader<R>, seen: &HashMap<u16, usize>) -> Result<u16, TournamentIOError> {
+    let offset = reader.offset();
+    let number = read_u16(reader)?;
+    if seen.contains_key(&number) {
+        return Err(TournamentIOError::DuplicatePlayerNumber(offset, number));
+    }
+
+    Ok(number)
+}
+
+/// seeds have to be players of the tournament and can only be seeded once
+fn read_bracket_seeds<R: BufRead>(reader: &mut OffsetReader<R>, players: &HashMap<u16, usize>) -> Result<Vec<u16>, TournamentIOError> {
+    let mut seeds = Vec::new();
+    for _ in 0..read_u16(reader)? {
+        let offset = reader.offset();
+        let number = read_u16(reader)?;
+        if !players.contains_key(&number) {
+            return Err(TournamentIOError::UnknownPlayer(offset, number));
+        }
+        if seeds.contains(&number) {
+            return Err(TournamentIOError::DuplicatePlayerNumber(offset, number));
+        }
+        seeds.push(number);
+    }
 
+    Ok(seeds)
+}
 
-        Ok(())
+fn read_flag<R: BufRead>(reader: &mut OffsetReader<R>) -> Result<bool, TournamentIOError> {
+    let offset = reader.offset();
+    let mut flag = [0_u8];
+    reader.read_exact(&mut flag)?;
+    match flag[0] {
+        0 => Ok(false),
+        1 => Ok(true),
+        e => Err(TournamentIOError::InvalidFlag(offset, e)),
     }
+}
 
-    pub fn write_to_file<P: AsRef<Path>>(&self, out_file: P) -> std::io::Result<()>{
-        let file = File::options()
-            .write(true)
-            // do this last? get everything into a vector and 1 big write?
-            // does wipe data consider better option?
-            .truncate(true)
-            .create(true)
-            .open(out_file)?;
+fn read_player_matches<R: BufRead>(
+    reader: &mut OffsetReader<R>,
+    name: String,
+    number: u16,
+    rounds: u16,
+    match_offsets: &mut HashMap<u16, usize>,
+) -> Result<Player, TournamentIOError> {
+    // a player keeps their record in bytes
+    if rounds > u8::MAX as u16 {
+        return Err(TournamentIOError::PlayerHasTooManyRounds(reader.offset(), u8::MAX as u16, rounds));
+    }
+    match_offsets.insert(number, reader.offset());
 
-        let mut writer = BufWriter::new(file);
+    let mut matches = Vec::new();
+    for _ in 0..rounds {
+        let opp_number = read_u16(reader)?;
+        let offset = reader.offset();
+        let mut outcome = [0_u8];
+        reader.read_exact(&mut outcome)?;
 
-        writer.write_all(self.name.as_bytes())?;
+        let outcome = match Outcome::try_from(outcome[0]) {
+            // byes used to be saved as wins against the bye
+            Ok(Outcome::Win) if opp_number == BYE_PLAYER_NUMBER => Outcome::Bye,
+            Ok(outcome) => outcome,
+            Err(e) => return Err(TournamentIOError::InvalidResultFound(offset, e)),
+        };
 
-        
-        writer.write_all(b"\n")?;
-        writer.write_all(&self.round_number.to_le_bytes())?;
-        writer.write_all(&(self.players.len() as u16).to_le_bytes())?;
-        // write out players
-        for player in &self.players {
-            writer.write_all(player.get_name().as_bytes())?;
-            writer.write_all(b"\n")?;
-            writer.write_all(&player.get_number().to_le_bytes())?;
-            // write protection?
-            for &(opp_num, outcome) in &player.get_matches()[..self.round_number as usize] {
-                writer.write_all(&opp_num.to_le_bytes())?;
-                writer.write_all(&[outcome as u8])?;
-            }
-        }
+        matches.push((opp_number, outcome));
+    }
 
-        writer.flush()?;
+    let count = |wanted: fn(Outcome) -> bool| matches.iter().filter(|&&(_, o)| wanted(o)).count() as u8;
+    let wins = count(|o| matches!(o, Outcome::Win | Outcome::ForfeitWin));
+    let losses = count(|o| matches!(o, Outcome::Loss | Outcome::DoubleLoss | Outcome::ForfeitLoss));
+    let ties = count(Outcome::is_draw);
 
-        Ok(())
+    Ok(Player::from_information(name, number, (wins, losses, ties), matches))
+}
+
+fn read_colours<R: BufRead>(reader: &mut OffsetReader<R>, player: &mut Player) -> Result<(), TournamentIOError> {
+    let offset = reader.offset();
+    let count = read_u16(reader)?;
+    // byes are played without a colour
+    let played = player.get_matches().len() as u16;
+    if count > played {
+        return Err(TournamentIOError::PlayerHasTooManyRounds(offset, played, count));
     }
 
-    pub fn read_from_file<P: AsRef<Path>>(in_file: P) -> Result<Self, TournamentIOError> {
-        let file = File::options().read(true).open(in_file)?;
-        let mut reader = BufReader::new(file);
-        
-        let mut name = String::new();
-        turn_eof_into_discriptive(reader.read_line(&mut name), TournamentIOError::EmptyFile)?;
-        // removes \n from name
-        if name.pop().is_none() {
-            return Err(TournamentIOError::EmptyFile);
-        }
-        
-        let mut round_number = [0_u8; 2];
-        turn_eof_into_discriptive(reader.read_exact(&mut round_number), TournamentIOError::MissingRoundNumber)?;
-        let round_number = u16::from_le_bytes(round_number);
-        
-        let mut number_of_players = [0_u8; 2];
-        turn_eof_into_discriptive(reader.read_exact(&mut number_of_players), TournamentIOError::MissingPlayerNumber)?;
-        let number_of_players = u16::from_le_bytes(number_of_players);
+    for _ in 0..count {
+        let offset = reader.offset();
+        let mut colour = [0_u8];
+        reader.read_exact(&mut colour)?;
+        let colour = match colour[0] {
+            0 => Colour::White,
+            1 => Colour::Black,
+            e => return Err(TournamentIOError::InvalidColourFound(offset, e)),
+        };
+        player.add_colour(colour);
+    }
 
-        let mut players: Vec<Player> = Vec::new();
-        for _ in 0..number_of_players {
-            let mut player_name = String::new();
-            reader.read_line(&mut player_name)?;
-            if player_name.pop().is_none() {
-                panic!("WERE ALL GONNA CRASH");
-            }
+    Ok(())
+}
 
-            let mut player_number = [0_u8; 2];
-            reader.read_exact(&mut player_number)?;
-            let player_number = u16::from_le_bytes(player_number);
-            dbg!(player_number);
-
-            let mut matches = Vec::new();
-            let mut wins = 0;
-            let mut ties = 0;
-            let mut losses = 0;
-
-            // something if player has more details, terminal value?
-            // add more data checks
-            for _ in 0..round_number {
-                let mut opp_number = [0_u8; 2];
-                reader.read_exact(&mut opp_number)?;
-                let mut outcome = [0_u8];
-                reader.read_exact(&mut outcome)?;
-                
-                let opp_number = u16::from_le_bytes(opp_number);
-                let outcome = match outcome[0] {
-                    0 => {
-                        wins += 1;
-                        Outcome::Win
-                    },
-                    1 => {
-                        losses += 1;
-                        Outcome::Loss
-                    },
-                    2 => {
-                        ties += 1;
-                        Outcome::Tie
-                    },
-                    e => return Err(TournamentIOError::InvalidResultFound(e)),
-                };
+/// Counts the bytes read so errors can say where in the file they were found
+struct OffsetReader<R> {
+    inner: R,
+    offset: usize,
+}
 
-                matches.push((opp_number, outcome));
-            }
-            players.push(Player::from_information(player_name, player_number, (wins,losses,ties), matches));
+impl<R: BufRead> OffsetReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            offset: 0,
         }
+    }
 
-        Ok(Self {
-            name,
-            round_number,
-            players,
-            pairings: Vec::new(),
-        })
+    fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<R: BufRead> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.offset += read;
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for OffsetReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.offset += amount;
     }
 }
 
@@ -211,12 +1813,51 @@ fn turn_eof_into_discriptive<T>(err: std::io::Result<T>, wanted: TournamentIOErr
     }
 }
 
+/// What is wrong with the pairings of a round, see [`Tournament::validate_pairings`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PairingCheck {
+    /// active players in no pairing, the round can not finish until they are paired
+    pub unpaired: Vec<u16>,
+    /// pairs of players who have met before
+    pub rematches: Vec<(u16, u16)>,
+}
+
+impl PairingCheck {
+    pub fn is_valid(&self) -> bool {
+        self.unpaired.is_empty()
+    }
+}
+
 #[derive(Debug)]
 pub enum TournamentError {
     RoundAlreadyStarted,
     RoundNotImprogress,
     InvalidMatchIndex(usize),
+    /// byes are declared when paired and can not be reported
+    MatchIsBye(usize),
+    /// elimination matches need a winner
+    TieInElimination(usize),
+    TopCutAlreadyStarted,
+    PlayerNotFound(u16),
     GamesNotFinished,
+    ResultsReported,
+    PlayersUnpaired(Vec<u16>),
+    NothingToUndo,
+    NothingToRedo,
+    Pairing(PairingError),
+    Bracket(BracketError),
+}
+
+impl From<PairingError> for TournamentError {
+    fn from(value: PairingError) -> Self {
+        Self::Pairing(value)
+    }
+}
+
+impl From<BracketError> for TournamentError {
+    fn from(value: BracketError) -> Self {
+        Self::Bracket(value)
+    }
 }
 
 impl Display for TournamentError {
@@ -225,7 +1866,17 @@ impl Display for TournamentError {
             Self::RoundAlreadyStarted => write!(f, "Attempt to start round when round has already been started"),
             Self::RoundNotImprogress => write!(f, "Attempted to do an opperation that needs a round in progress"),
             Self::InvalidMatchIndex(idx) => write!(f, "Given index of {} is out of bounds", idx),
+            Self::MatchIsBye(idx) => write!(f, "Match {} is a bye and has no result to report", idx),
+            Self::TieInElimination(idx) => write!(f, "Match {} is an elimination match and needs a winner", idx),
+            Self::TopCutAlreadyStarted => write!(f, "The top cut has already been started"),
+            Self::PlayerNotFound(number) => write!(f, "No player with the number {} can be found", number),
             Self::GamesNotFinished => write!(f, "Attempted to end tournament with rounds still in progress"),
+            Self::ResultsReported => write!(f, "Results have already been reported for this round"),
+            Self::PlayersUnpaired(numbers) => write!(f, "Players {:?} are not in any pairing", numbers),
+            Self::NothingToUndo => write!(f, "There is nothing to undo"),
+            Self::NothingToRedo => write!(f, "There is nothing to redo"),
+            Self::Pairing(e) => write!(f, "{}", e),
+            Self::Bracket(e) => write!(f, "{}", e),
         }
     }
 }
@@ -235,14 +1886,71 @@ impl Error for TournamentError {}
 #[derive(Debug)]
 pub enum TournamentIOError {
     Io(std::io::Error),
+    /// a name or tag ran to the end of the file
     MissingNewLineSeperator(usize),
-    PlayerHasTooManyRounds(u16, u16),
-    InvalidResultFound(u8),
+    /// more rounds than the tournament has had, the most there could be then what was found
+    PlayerHasTooManyRounds(usize, u16, u16),
+    InvalidResultFound(usize, u8),
+    InvalidColourFound(usize, u8),
+    /// a yes or no that was neither
+    InvalidFlag(usize, u8),
+    InvalidBracket(usize, BracketError),
+    InvalidBracketKind(usize, u8),
+    InvalidByeKind(usize, u8),
+    InvalidTagPolicy(usize, u8),
+    InvalidPairingSystem(usize, u8),
+    InvalidRematchPolicy(usize, u8),
+    InvalidTiebreaker(usize, u8),
+    InvalidFloatReason(usize, u8),
+    InvalidByeSkip(usize, u8),
+    InvalidUtf8(usize),
+    DuplicatePlayerNumber(usize, u16),
+    /// a match, pairing or seed naming a player who is not in the tournament
+    UnknownPlayer(usize, u16),
+    PlayedThemselves(usize, u16),
+    /// the two players of a match saved different results for it
+    AsymmetricResult(usize, u16, u16),
+    /// the file goes on after the last section
+    TrailingData(usize),
+    /// the file ended part way through, the offset is its length
+    UnexpectedEnd(usize),
+    /// the file was saved by a newer version
+    UnsupportedVersion(u16),
     EmptyFile,
     MissingRoundNumber,
     MissingPlayerNumber,
 }
 
+impl TournamentIOError {
+    /// where in the file the problem was found, counting bytes from the start
+    pub fn offset(&self) -> Option<usize> {
+        match *self {
+            Self::MissingNewLineSeperator(offset)
+            | Self::PlayerHasTooManyRounds(offset, _, _)
+            | Self::InvalidResultFound(offset, _)
+            | Self::InvalidColourFound(offset, _)
+            | Self::InvalidFlag(offset, _)
+            | Self::InvalidBracket(offset, _)
+            | Self::InvalidBracketKind(offset, _)
+            | Self::InvalidByeKind(offset, _)
+            | Self::InvalidTagPolicy(offset, _)
+            | Self::InvalidPairingSystem(offset, _)
+            | Self::InvalidRematchPolicy(offset, _)
+            | Self::InvalidTiebreaker(offset, _)
+            | Self::InvalidFloatReason(offset, _)
+            | Self::InvalidByeSkip(offset, _)
+            | Self::InvalidUtf8(offset)
+            | Self::DuplicatePlayerNumber(offset, _)
+            | Self::UnknownPlayer(offset, _)
+            | Self::PlayedThemselves(offset, _)
+            | Self::AsymmetricResult(offset, _, _)
+            | Self::TrailingData(offset)
+            | Self::UnexpectedEnd(offset) => Some(offset),
+            Self::Io(_) | Self::UnsupportedVersion(_) | Self::EmptyFile | Self::MissingRoundNumber | Self::MissingPlayerNumber => None,
+        }
+    }
+}
+
 impl From<std::io::Error> for TournamentIOError {
     fn from(value: std::io::Error) -> Self {
         Self::Io(value)
@@ -254,8 +1962,27 @@ impl Display for TournamentIOError {
         match self {
             Self::Io(e) => write!(f, "{}", e),
             Self::MissingNewLineSeperator(pos) => write!(f, "expected newline at byte position: {}", pos),
-            Self::PlayerHasTooManyRounds(expected, found) => write!(f, "player has played {} rounds expected {}", found, expected),
-            Self::InvalidResultFound(err_res) => write!(f, "found {} in result value should be 0,1,2", err_res),
+            Self::PlayerHasTooManyRounds(pos, most, found) => write!(f, "player has played {} rounds expected at most {} at byte {}", found, most, pos),
+            Self::InvalidResultFound(pos, err_res) => write!(f, "found {} in result value should be 0 to 7 at byte {}", err_res, pos),
+            Self::InvalidColourFound(pos, err_col) => write!(f, "found {} in colour value should be 0,1 at byte {}", err_col, pos),
+            Self::InvalidFlag(pos, flag) => write!(f, "found {} in flag should be 0,1 at byte {}", flag, pos),
+            Self::InvalidBracket(pos, e) => write!(f, "saved bracket is invalid: {} at byte {}", e, pos),
+            Self::InvalidBracketKind(pos, kind) => write!(f, "found {} in bracket kind should be 0,1,2 at byte {}", kind, pos),
+            Self::InvalidByeKind(pos, kind) => write!(f, "found {} in bye kind should be 0,1,2 at byte {}", kind, pos),
+            Self::InvalidTagPolicy(pos, kind) => write!(f, "found {} in tag policy should be 0,1,2 at byte {}", kind, pos),
+            Self::InvalidPairingSystem(pos, kind) => write!(f, "found {} in pairing system should be 0 to 4 at byte {}", kind, pos),
+            Self::InvalidRematchPolicy(pos, kind) => write!(f, "found {} in rematch policy should be 0,1 at byte {}", kind, pos),
+            Self::InvalidTiebreaker(pos, kind) => write!(f, "found {} in tiebreaker should be 0 to 8 at byte {}", kind, pos),
+            Self::InvalidFloatReason(pos, kind) => write!(f, "found {} in float reason should be 0 to 3 at byte {}", kind, pos),
+            Self::InvalidByeSkip(pos, kind) => write!(f, "found {} in bye skip should be 0 or 1 at byte {}", kind, pos),
+            Self::InvalidUtf8(pos) => write!(f, "name is not valid UTF-8 at byte {}", pos),
+            Self::DuplicatePlayerNumber(pos, number) => write!(f, "player number {} is used twice at byte {}", number, pos),
+            Self::UnknownPlayer(pos, number) => write!(f, "player {} is not in the tournament at byte {}", number, pos),
+            Self::PlayedThemselves(pos, number) => write!(f, "player {} is saved as playing themselves at byte {}", number, pos),
+            Self::AsymmetricResult(pos, number, opponent) => write!(f, "players {} and {} saved different results for their match at byte {}", number, opponent, pos),
+            Self::TrailingData(pos) => write!(f, "unexpected data after the end of the tournament at byte {}", pos),
+            Self::UnexpectedEnd(pos) => write!(f, "file ended part way through at byte {}", pos),
+            Self::UnsupportedVersion(version) => write!(f, "file version {} is newer than the supported version {}", version, FORMAT_VERSION),
             Self::EmptyFile => write!(f, "was given an empty file"),
             Self::MissingRoundNumber => write!(f, "Expected to find 16 bit round number"),
             Self::MissingPlayerNumber => write!(f, "Expected to find 16 bit number of players")
@@ -268,6 +1995,7 @@ impl Error for TournamentIOError {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tiebreakers;
 
     fn generate_players(number: u16) -> Vec<Player> {
         (1..number+1).map(|num| Player::new(num.to_string(), num)).collect()
@@ -281,7 +2009,8 @@ mod tests {
     #[test]
     fn just_read_name() {
         let tournament = Tournament::read_from_file("../test-files/name_bare_min.sts").unwrap();
-        let test_tournmanet = Tournament::new("My Name".to_string(), Vec::new());
+        let mut test_tournmanet = Tournament::new("My Name".to_string(), Vec::new());
+        test_tournmanet.set_seed(tournament.get_seed());
         assert_eq!(tournament, test_tournmanet);
     }
 
@@ -290,7 +2019,8 @@ mod tests {
         dbg!("HIII");
         let tournament = Tournament::read_from_file("../test-files/valid_no_rounds.sts").unwrap();
         let players = generate_players(4);
-        let test_tournmanet = Tournament::new("Tournament name".to_string(), players);
+        let mut test_tournmanet = Tournament::new("Tournament name".to_string(), players);
+        test_tournmanet.set_seed(tournament.get_seed());
         assert_eq!(tournament, test_tournmanet);
     }
 
@@ -324,4 +2054,730 @@ mod tests {
 
         assert!(matches!(tournament_error, TournamentIOError::MissingPlayerNumber));
     }
+
+    fn pairing_numbers(tournament: &Tournament) -> Vec<(u16, Option<u16>)> {
+        tournament
+            .get_pairings()
+            .iter()
+            .map(|p| {
+                let (p1, p2) = p.get_players();
+                (p1.get_number(), p2.map(|p| p.get_number()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_pairings() {
+        let mut first = Tournament::new("Seeded".to_string(), generate_players(16));
+        let mut second = Tournament::new("Seeded".to_string(), generate_players(16));
+        first.set_seed(0x5eed);
+        second.set_seed(0x5eed);
+
+        for _ in 0..3 {
+            first.start_round().unwrap();
+            second.start_round().unwrap();
+            assert_eq!(pairing_numbers(&first), pairing_numbers(&second));
+
+            for idx in 0..first.get_pairings().len() {
+                first.report_match(idx, Outcome::Win).unwrap();
+                second.report_match(idx, Outcome::Win).unwrap();
+            }
+            first.finilze_round().unwrap();
+            second.finilze_round().unwrap();
+        }
+    }
+
+    #[test]
+    fn seed_is_saved() {
+        let mut tournament = Tournament::new("Seeded".to_string(), generate_players(4));
+        tournament.set_seed(u64::MAX - 7);
+
+        let path = std::env::temp_dir().join("tcg_swiss_seed_is_saved.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.get_seed(), u64::MAX - 7);
+        assert_eq!(read, tournament);
+    }
+
+    #[test]
+    fn second_bye_when_everyone_had_one() {
+        let players = generate_players(3)
+            .into_iter()
+            .map(|mut p| {
+                p.add_match(BYE_PLAYER_NUMBER, Outcome::Bye, None);
+                p
+            })
+            .collect();
+        let mut tournament = Tournament::new("Byes".to_string(), players);
+        assert_eq!(tournament.get_rematch_policy(), RematchPolicy::Never);
+
+        tournament.start_round().unwrap();
+        assert_eq!(tournament.get_pairings().iter().filter(|p| p.is_bye()).count(), 1);
+    }
+
+    #[test]
+    fn bye_goes_to_lowest_without_one() {
+        let mut tournament = Tournament::new("Byes".to_string(), generate_players(5));
+        tournament.set_scoring(ScoreConfig {
+            bye: ByeValue::Points(Points::ZERO),
+            ..ScoreConfig::default()
+        });
+
+        let mut byes = Vec::new();
+        for _ in 0..2 {
+            tournament.start_round().unwrap();
+            let last = tournament.get_pairings().len() - 1;
+            assert!(matches!(tournament.report_match(last, Outcome::Win), Err(TournamentError::MatchIsBye(_))));
+
+            let bye = tournament.get_pairing(last).get_players().0;
+            let lowest = tournament.ranked_players().last().unwrap().caluculate_match_points(tournament.get_scoring());
+            assert_eq!(bye.caluculate_match_points(tournament.get_scoring()), lowest);
+            byes.push(bye.get_number());
+
+            for idx in 0..last {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        assert_ne!(byes[0], byes[1]);
+        let bye = tournament.get_players().iter().find(|p| p.get_number() == byes[0]).unwrap();
+        assert_eq!(bye.get_matches()[0], (BYE_PLAYER_NUMBER, Outcome::Bye));
+        assert_eq!(bye.get_byes(), 1);
+    }
+
+    #[test]
+    fn dropped_players_keep_their_record() {
+        let mut tournament = Tournament::new("Drops".to_string(), generate_players(5));
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::Win).unwrap();
+        assert!(matches!(tournament.drop_player(1), Err(TournamentError::ResultsReported)));
+        for idx in 0..tournament.get_pairings().len() - 1 {
+            tournament.report_match(idx, Outcome::Win).unwrap();
+        }
+        tournament.finilze_round().unwrap();
+
+        tournament.drop_player(3).unwrap();
+        assert!(matches!(tournament.drop_player(3), Err(TournamentError::PlayerNotFound(3))));
+        assert!(tournament.is_dropped(3));
+
+        tournament.start_round().unwrap();
+        assert_eq!(tournament.get_pairings().len(), 2);
+        assert!(tournament.get_pairings().iter().all(|p| !p.is_bye()));
+        assert!(tournament.standings().iter().any(|s| s.player_number == 3));
+        for idx in 0..2 {
+            tournament.report_match(idx, Outcome::Tie).unwrap();
+        }
+        tournament.finilze_round().unwrap();
+
+        let path = std::env::temp_dir().join("tcg_swiss_dropped_players.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, tournament);
+
+        let record = tournament.get_dropped_players()[0].get_matches()[0];
+        tournament.undrop_player(3).unwrap();
+        let player = tournament.get_players().iter().find(|p| p.get_number() == 3).unwrap();
+        assert_eq!(player.get_matches(), &[record, (BYE_PLAYER_NUMBER, Outcome::Loss)]);
+    }
+
+    #[test]
+    fn top_cut_is_seeded_from_standings() {
+        let mut tournament = Tournament::new("Top Cut".to_string(), generate_players(12));
+        for _ in 0..3 {
+            tournament.start_round().unwrap();
+            for idx in 0..tournament.get_pairings().len() {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        let standings = tournament.standings().iter().map(|s| s.player_number).collect::<Vec<u16>>();
+        tournament.start_top_cut(8).unwrap();
+        assert!(matches!(tournament.start_top_cut(8), Err(TournamentError::TopCutAlreadyStarted)));
+        // a seed leaving would leave a hole in the bracket
+        assert!(matches!(tournament.drop_player(standings[0]), Err(TournamentError::TopCutAlreadyStarted)));
+        assert!(matches!(tournament.drop_player(standings[11]), Err(TournamentError::TopCutAlreadyStarted)));
+
+        tournament.start_round().unwrap();
+        assert_eq!(tournament.get_pairings().len(), 4);
+        let (top, bottom) = tournament.get_pairing(0).get_players();
+        assert_eq!((top.get_number(), bottom.unwrap().get_number()), (standings[0], standings[7]));
+        let (top, bottom) = tournament.get_pairing(1).get_players();
+        assert_eq!((top.get_number(), bottom.unwrap().get_number()), (standings[3], standings[4]));
+        assert!(matches!(tournament.report_match(0, Outcome::Tie), Err(TournamentError::TieInElimination(0))));
+
+        // the bottom seed wins every match
+        while !tournament.get_top_cut().unwrap().is_finished() {
+            if tournament.get_pairings().is_empty() {
+                tournament.start_round().unwrap();
+            }
+            for idx in 0..tournament.get_pairings().len() {
+                tournament.report_match(idx, Outcome::Loss).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+
+        assert_eq!(tournament.get_players().len(), 12);
+        assert_eq!(tournament.standings().iter().map(|s| s.player_number).collect::<Vec<u16>>(), standings);
+        assert!(matches!(tournament.start_round(), Err(TournamentError::Bracket(BracketError::Finished))));
+
+        let path = std::env::temp_dir().join("tcg_swiss_top_cut.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.get_top_cut(), tournament.get_top_cut());
+        assert_eq!(read.get_top_cut().unwrap().champion(), Some(standings[7]));
+    }
+
+    #[test]
+    fn double_elimination_instead_of_swiss() {
+        let mut tournament = Tournament::new("Double".to_string(), generate_players(6));
+        tournament.start_double_elimination(true).unwrap();
+
+        let mut rounds = 0;
+        while !tournament.get_top_cut().unwrap().is_finished() {
+            tournament.start_round().unwrap();
+            for idx in 0..tournament.get_pairings().len() {
+                tournament.report_match(idx, Outcome::Win).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+            rounds += 1;
+
+            let path = std::env::temp_dir().join("tcg_swiss_double_elimination.sts");
+            tournament.write_to_file(&path).unwrap();
+            let read = Tournament::read_from_file(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(read.get_top_cut(), tournament.get_top_cut());
+        }
+
+        // the better seed always won so the winners bracket champion takes it without a reset
+        let champion = tournament.get_top_cut().unwrap().champion().unwrap();
+        assert_eq!(champion, tournament.standings()[0].player_number);
+        assert!(rounds >= 5);
+        assert_eq!(tournament.get_players().len(), 6);
+        assert!(tournament.get_players().iter().all(|p| p.get_matches().is_empty()));
+    }
+
+    #[test]
+    fn game_scores_are_kept() {
+        let mut tournament = Tournament::new("Best of three".to_string(), generate_players(4));
+        tournament.start_round().unwrap();
+        tournament.report_games(0, GameScore::new(2, 1, 0)).unwrap();
+        tournament.report_games(1, GameScore::new(1, 1, 1)).unwrap();
+        assert_eq!(tournament.get_pairing(0).get_outcome(), Some(Outcome::Win));
+        assert_eq!(tournament.get_pairing(1).get_outcome(), Some(Outcome::Tie));
+
+        let winner = tournament.get_pairing(0).get_players().0.get_number();
+        let loser = tournament.get_pairing(0).get_players().1.unwrap().get_number();
+        tournament.finilze_round().unwrap();
+
+        let winner = tournament.get_players().iter().find(|p| p.get_number() == winner).unwrap();
+        assert!((tiebreakers::game_win_percentage(winner) - 2.0 / 3.0).abs() < 1e-6);
+
+        let loser = tournament.get_players().iter().find(|p| p.get_number() == loser).unwrap();
+        assert_eq!(loser.get_games(), &[Some(GameScore::new(1, 2, 0))]);
+        assert_eq!(loser.get_record(), (0, 1, 0));
+        assert!((tiebreakers::game_win_percentage(loser) - 1.0 / 3.0).abs() < 1e-6);
+
+        let path = std::env::temp_dir().join("tcg_swiss_game_scores.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, tournament);
+    }
+
+    #[test]
+    fn half_points_and_scoring_are_saved() {
+        let players = (1..=4).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::with_scoring("Chess".to_string(), players, crate::CHESS_SCORING);
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::Win).unwrap();
+        tournament.report_match(1, Outcome::Tie).unwrap();
+        tournament.finilze_round().unwrap();
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::Win).unwrap();
+        tournament.report_match(1, Outcome::Win).unwrap();
+        tournament.finilze_round().unwrap();
+
+        let points = tournament.standings().iter().map(|s| s.match_points.to_string()).collect::<Vec<String>>();
+        assert!(points.contains(&"1.5".to_string()));
+
+        let scoring = ScoreConfig {
+            bye: ByeValue::Points(Points::from_halves(3)),
+            double_loss: Points::HALF,
+            ..crate::DEFUALT_SCORING
+        };
+        tournament.set_scoring(scoring);
+        let path = std::env::temp_dir().join("tcg_swiss_scoring.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.get_scoring(), scoring);
+        assert_eq!(read, tournament);
+    }
+
+    #[test]
+    fn large_point_values_do_not_overflow() {
+        let mut player = Player::new("Grinder".to_string(), 1);
+        for round in 0..200 {
+            player.add_opponent(round + 2, Outcome::Win);
+        }
+        let scoring = ScoreConfig { win: Points::new(100), ..crate::DEFUALT_SCORING };
+        assert_eq!(player.caluculate_match_points(scoring), Points::new(20_000));
+    }
+
+    #[test]
+    fn judge_outcomes_are_recorded() {
+        let players = (1..=6).map(|n| Player::new(n.to_string(), n)).collect();
+        let scoring = ScoreConfig { double_loss: Points::HALF, ..crate::DEFUALT_SCORING };
+        let mut tournament = Tournament::with_scoring("Judges".to_string(), players, scoring);
+        tournament.start_round().unwrap();
+        let numbers = tournament
+            .get_pairings()
+            .iter()
+            .map(|p| (p.get_players().0.get_number(), p.get_players().1.unwrap().get_number()))
+            .collect::<Vec<(u16, u16)>>();
+        tournament.report_match(0, Outcome::DoubleLoss).unwrap();
+        tournament.report_match(1, Outcome::IntentionalDraw).unwrap();
+        tournament.report_match(2, Outcome::ForfeitWin).unwrap();
+        tournament.finilze_round().unwrap();
+
+        let get = |n: u16| tournament.get_players().iter().find(|p| p.get_number() == n).unwrap();
+
+        for n in [numbers[0].0, numbers[0].1] {
+            assert_eq!(get(n).get_record(), (0, 1, 0));
+            assert_eq!(get(n).get_double_losses(), 1);
+            assert_eq!(get(n).caluculate_match_points(scoring), Points::HALF);
+        }
+        for n in [numbers[1].0, numbers[1].1] {
+            assert_eq!(get(n).get_record(), (0, 0, 1));
+            assert_eq!(get(n).get_intentional_draws(), 1);
+        }
+        let (winner, loser) = numbers[2];
+        assert_eq!(get(winner).get_matches(), &[(loser, Outcome::ForfeitWin)]);
+        assert_eq!(get(loser).get_matches(), &[(winner, Outcome::ForfeitLoss)]);
+        assert_eq!(get(winner).caluculate_match_points(scoring), Points::new(3));
+        // no games were played so there is nothing for game win percentage to use
+        assert_eq!(tiebreakers::game_win_percentage(get(winner)), tiebreakers::PERCENTAGE_FLOOR);
+
+        let path = std::env::temp_dir().join("tcg_swiss_judge_outcomes.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, tournament);
+    }
+
+    #[test]
+    fn repair_round_with_late_player() {
+        let players = (1..=4).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Repair".to_string(), players);
+        tournament.start_round().unwrap();
+        tournament.add_player(Player::new("Late".to_string(), 5));
+        tournament.add_player(Player::new("Later".to_string(), 6));
+        tournament.repair_round(false).unwrap();
+        assert_eq!(tournament.get_pairings().len(), 3);
+        assert!(tournament.get_players().is_empty());
+
+        tournament.report_match(0, Outcome::Win).unwrap();
+        assert!(matches!(tournament.repair_round(false), Err(TournamentError::ResultsReported)));
+        assert_eq!(tournament.get_pairing(0).get_outcome(), Some(Outcome::Win));
+
+        tournament.repair_round(true).unwrap();
+        assert!(tournament.get_pairings().iter().all(|p| !p.is_delcared()));
+        assert_eq!(tournament.iter_all_players().count(), 6);
+    }
+
+    #[test]
+    fn repair_round_with_dropped_player() {
+        let players = (1..=6).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Repair".to_string(), players);
+        tournament.start_round().unwrap();
+        let paired = tournament.get_pairings().to_vec();
+
+        // the same players and seed pair the same way
+        tournament.repair_round(false).unwrap();
+        assert_eq!(tournament.get_pairings(), paired);
+
+        tournament.drop_player(2).unwrap();
+        assert_eq!(tournament.get_pairings().len(), 3);
+        assert!(tournament.get_pairings()[2].is_bye());
+        let seated = tournament.get_pairings().iter().flat_map(|p| {
+            let (p1, p2) = p.get_players();
+            [Some(p1), p2]
+        });
+        assert!(seated.flatten().all(|p| p.get_number() != 2));
+        assert!(tournament.get_players().is_empty());
+        assert!(tournament.is_dropped(2));
+
+        tournament.undrop_player(2).unwrap();
+        assert_eq!(tournament.get_pairings(), paired);
+        assert!(tournament.get_dropped_players().is_empty());
+
+        tournament.report_match(0, Outcome::Win).unwrap();
+        assert!(matches!(tournament.drop_player(2), Err(TournamentError::ResultsReported)));
+        assert!(matches!(tournament.drop_player(9), Err(TournamentError::ResultsReported)));
+        assert_eq!(tournament.get_pairing(0).get_outcome(), Some(Outcome::Win));
+        assert_eq!(tournament.iter_all_players().count(), 6);
+    }
+
+    #[test]
+    fn manual_pairing_changes() {
+        let players = (1..=4).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Manual".to_string(), players);
+        tournament.create_pairing(1, Some(2)).unwrap();
+        assert!(matches!(tournament.create_pairing(3, Some(3)), Err(TournamentError::PlayerNotFound(3))));
+        assert_eq!(tournament.validate_pairings().unpaired, vec![3, 4]);
+        tournament.create_pairing(3, Some(4)).unwrap();
+        assert!(tournament.validate_pairings().is_valid());
+        for idx in 0..2 {
+            tournament.report_match(idx, Outcome::Win).unwrap();
+        }
+        tournament.finilze_round().unwrap();
+
+        // family members 1 and 3 get split up by hand
+        tournament.create_pairing(1, Some(3)).unwrap();
+        tournament.create_pairing(2, Some(4)).unwrap();
+        tournament.report_match(0, Outcome::Win).unwrap();
+        tournament.swap_seats((0, Colour::Black), (1, Colour::White)).unwrap();
+        assert_eq!(tournament.get_pairing(0).get_outcome(), None);
+        let seats = |t: &Tournament, idx: usize| {
+            let (p1, p2) = t.get_pairing(idx).get_players();
+            (p1.get_number(), p2.unwrap().get_number())
+        };
+        assert_eq!(seats(&tournament, 0), (1, 2));
+        assert_eq!(seats(&tournament, 1), (3, 4));
+        assert_eq!(tournament.validate_pairings().rematches, vec![(1, 2), (3, 4)]);
+
+        tournament.swap_seats((1, Colour::White), (1, Colour::Black)).unwrap();
+        assert_eq!(seats(&tournament, 1), (4, 3));
+
+        tournament.delete_pairing(1).unwrap();
+        assert!(matches!(tournament.finilze_round(), Err(TournamentError::GamesNotFinished)));
+        tournament.report_match(0, Outcome::Tie).unwrap();
+        assert!(matches!(tournament.finilze_round(), Err(TournamentError::PlayersUnpaired(_))));
+        tournament.create_pairing(3, None).unwrap();
+        tournament.create_pairing(4, None).unwrap();
+        tournament.finilze_round().unwrap();
+        assert_eq!(tournament.get_round_number(), 2);
+    }
+
+    fn tagged_players(tags: &[&str]) -> Vec<Player> {
+        tags.iter()
+            .zip(1..)
+            .map(|(&tag, n)| Player::with_tag(n.to_string(), n, tag.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn teams_are_never_paired() {
+        for system in [PairingSystem::Greedy, PairingSystem::Matching, PairingSystem::Dutch] {
+            let players = tagged_players(&["A", "A", "A", "A", "B", "B", "B", "B"]);
+            let mut tournament = Tournament::new("Teams".to_string(), players);
+            tournament.set_pairing_system(system);
+            tournament.set_tag_policy(TagPolicy::Never);
+            for _ in 0..3 {
+                tournament.start_round().unwrap();
+                for pairing in tournament.get_pairings() {
+                    let (p1, p2) = pairing.get_players();
+                    assert!(!p1.shares_tag(p2.unwrap()), "{:?}", system);
+                }
+                for idx in 0..4 {
+                    tournament.report_match(idx, Outcome::Win).unwrap();
+                }
+                tournament.finilze_round().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn only_needed_tags_are_relaxed() {
+        // four of one team can be kept down to a single pair of them meeting
+        for system in [PairingSystem::Greedy, PairingSystem::Matching, PairingSystem::Dutch] {
+            for seed in 0..20 {
+                let players = tagged_players(&["A", "A", "A", "A", "B", "C"]);
+                let mut tournament = Tournament::new("Teams".to_string(), players);
+                tournament.set_pairing_system(system);
+                tournament.set_tag_policy(TagPolicy::AvoidUntil(3));
+                tournament.set_seed(seed);
+                tournament.start_round().unwrap();
+
+                let clashes = tournament
+                    .get_pairings()
+                    .iter()
+                    .filter(|p| matches!(p.get_players(), (p1, Some(p2)) if p1.shares_tag(p2)))
+                    .count();
+                assert_eq!(clashes, 1, "{:?} seed {}", system, seed);
+                assert_eq!(tournament.get_relaxed_tags().len(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_teammates_can_not_pair() {
+        let mut tags = vec!["A"; 13];
+        tags.extend(["B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"]);
+        for system in [PairingSystem::Greedy, PairingSystem::Matching, PairingSystem::Dutch] {
+            let mut tournament = Tournament::new("Teams".to_string(), tagged_players(&tags));
+            tournament.set_pairing_system(system);
+            tournament.set_tag_policy(TagPolicy::Never);
+            assert!(matches!(tournament.start_round(), Err(TournamentError::Pairing(PairingError::NoLegalPairing))), "{:?}", system);
+            assert_eq!(tournament.get_players().len(), 24);
+        }
+    }
+
+    #[test]
+    fn soft_tags_are_relaxed() {
+        let players = tagged_players(&["Family", "Family", "Family", "Friend"]);
+        let mut tournament = Tournament::new("Store".to_string(), players);
+        tournament.set_tag_policy(TagPolicy::Never);
+        assert!(matches!(tournament.start_round(), Err(TournamentError::Pairing(PairingError::NoLegalPairing))));
+
+        tournament.set_tag_policy(TagPolicy::AvoidUntil(3));
+        tournament.start_round().unwrap();
+        let relaxed = tournament.get_relaxed_tags().to_vec();
+        assert_eq!(relaxed.len(), 1);
+        assert!(relaxed[0].0 <= 3 && relaxed[0].1 <= 3);
+        for idx in 0..2 {
+            tournament.report_match(idx, Outcome::Win).unwrap();
+        }
+        tournament.finilze_round().unwrap();
+        assert!(tournament.get_relaxed_tags().is_empty());
+
+        tournament.set_player_tag(4, None).unwrap();
+        let path = std::env::temp_dir().join("tcg_swiss_tags.sts");
+        tournament.write_to_file(&path).unwrap();
+        let read = Tournament::read_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.get_tag_policy(), TagPolicy::AvoidUntil(3));
+        assert_eq!(read, tournament);
+    }
+
+    #[test]
+    fn tags_only_avoided_until_round() {
+        assert!(TagPolicy::AvoidUntil(3).separates(0));
+        assert!(TagPolicy::AvoidUntil(3).separates(1));
+        assert!(!TagPolicy::AvoidUntil(3).separates(2));
+        assert!(TagPolicy::Never.separates(10));
+        assert!(!TagPolicy::Ignore.separates(0));
+    }
+
+    #[test]
+    fn unversioned_files_migrate() {
+        let mut tournament = Tournament::read_from_file("../test-files/valid_no_rounds.sts").unwrap();
+        tournament.set_scoring(crate::CHESS_SCORING);
+
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+        assert_eq!(&bytes[..4], MAGIC);
+        assert_eq!(u16::from_le_bytes([bytes[4], bytes[5]]), FORMAT_VERSION);
+        assert_eq!(Tournament::read_from(&bytes[..]).unwrap(), tournament);
+
+        bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(Tournament::read_from(&bytes[..]), Err(TournamentIOError::UnsupportedVersion(v)) if v == FORMAT_VERSION + 1));
+    }
+
+    #[test]
+    fn equal_when_saved_the_same() {
+        let mut tournament = Tournament::new("Equal".to_string(), generate_players(4));
+        tournament.set_seed(1);
+        let mut other = tournament.clone();
+
+        // a change undone leaves history behind but the same tournament
+        other.set_auditing(true);
+        other.undo().unwrap();
+        assert_eq!(other.get_journal().get_undone().len(), 1);
+        assert_eq!(other, tournament);
+
+        other.set_auditing(true);
+        assert_ne!(other, tournament);
+    }
+
+    #[test]
+    fn audits_round_trip() {
+        let mut tournament = Tournament::new("Audits".to_string(), generate_players(8));
+        tournament.set_seed(3);
+        tournament.set_auditing(true);
+        for _ in 0..2 {
+            tournament.start_round().unwrap();
+            // a tie at the last table leaves odd brackets so someone floats
+            for idx in 0..4 {
+                tournament.report_match(idx, if idx == 3 { Outcome::Tie } else { Outcome::Win }).unwrap();
+            }
+            tournament.finilze_round().unwrap();
+        }
+        let audits = tournament.get_audits();
+        assert_eq!(audits.len(), 2);
+        assert!(!audits[1].floats.is_empty());
+
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+        let read = Tournament::read_from(&bytes[..]).unwrap();
+        assert!(read.is_auditing());
+        assert_eq!(read.get_audits(), audits);
+        assert_eq!(read.audit_report(), tournament.audit_report());
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let mut tournament = Tournament::new("Settings".to_string(), generate_players(6));
+        tournament.set_pairing_system(PairingSystem::Dutch);
+        tournament.set_rematch_policy(RematchPolicy::AllowIfUnavoidable);
+        tournament.set_tiebreakers(vec![TiebreakerKind::SonnebornBerger, TiebreakerKind::DirectEncounter, TiebreakerKind::BuchholzCut1]);
+
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+        let read = Tournament::read_from(&bytes[..]).unwrap();
+        assert_eq!(read.get_pairing_system(), PairingSystem::Dutch);
+        assert_eq!(read.get_rematch_policy(), RematchPolicy::AllowIfUnavoidable);
+        assert_eq!(read.get_tiebreakers(), tournament.get_tiebreakers());
+        assert_eq!(read, tournament);
+
+        // the final tiebreaker comes just before the seating and auditing flags and no audits
+        let last = bytes.len() - 5;
+        bytes[last] = 9;
+        assert!(matches!(Tournament::read_from(&bytes[..]), Err(TournamentIOError::InvalidTiebreaker(offset, 9)) if offset == last));
+    }
+
+    #[test]
+    fn versioned_sections_are_required() {
+        let tournament = Tournament::new("Cut Short".to_string(), vec![Player::new("A".to_string(), 1)]);
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+
+        // an unversioned file could end after the players but a versioned one can not
+        let players_end = 6 + "Cut Short\n".len() + 4 + "A\n".len() + 2;
+        assert!(Tournament::read_from(&bytes[..players_end]).is_err());
+        assert!(Tournament::read_from(&bytes[6..players_end]).is_ok());
+    }
+
+    #[test]
+    fn damaged_files_are_errors() {
+        let mut tournament = Tournament::new("Damaged".to_string(), generate_players(4));
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::Win).unwrap();
+        tournament.report_match(1, Outcome::Tie).unwrap();
+        tournament.finilze_round().unwrap();
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+        let read = |bytes: &[u8]| Tournament::read_from(bytes).unwrap_err();
+
+        assert!(matches!(read(b"No Newline"), TournamentIOError::MissingNewLineSeperator(10)));
+        assert!(matches!(read(&bytes[..bytes.len() - 1]), TournamentIOError::UnexpectedEnd(end) if end == bytes.len() - 1));
+        assert!(matches!(read(&[&bytes[..], b"!"].concat()), TournamentIOError::TrailingData(end) if end == bytes.len()));
+
+        let mut bad_name = bytes.clone();
+        bad_name[8] = 0xFF;
+        assert!(matches!(read(&bad_name), TournamentIOError::InvalidUtf8(8)));
+
+        // the first player starts after the header, name, round and player count
+        let first = tournament.get_players()[0].get_number();
+        let number = 6 + "Damaged\n".len() + 4 + "1\n".len();
+        // then comes their single match and the next players name
+        let second = number + 2 + 3 + "2\n".len();
+        let mut duplicate = bytes.clone();
+        duplicate[second..second + 2].copy_from_slice(&first.to_le_bytes());
+        assert!(matches!(read(&duplicate), TournamentIOError::DuplicatePlayerNumber(offset, n) if offset == second && n == first));
+
+        let mut unknown = bytes.clone();
+        unknown[number + 2..number + 4].copy_from_slice(&999_u16.to_le_bytes());
+        assert!(matches!(read(&unknown), TournamentIOError::UnknownPlayer(offset, 999) if offset == number + 2));
+
+        let mut themselves = bytes.clone();
+        themselves[number + 2..number + 4].copy_from_slice(&first.to_le_bytes());
+        assert!(matches!(read(&themselves), TournamentIOError::PlayedThemselves(offset, n) if offset == number + 2 && n == first));
+
+        let mut result = bytes.clone();
+        result[number + 4] = if result[number + 4] == Outcome::Win as u8 { Outcome::Tie as u8 } else { Outcome::Win as u8 };
+        assert!(matches!(read(&result), TournamentIOError::AsymmetricResult(offset, n, _) if offset == number + 2 && n == first));
+        result[number + 4] = 9;
+        assert!(matches!(read(&result), TournamentIOError::InvalidResultFound(offset, 9) if offset == number + 4));
+    }
+
+    /// every file of the fuzzing corpus is read without a panic, the good ones saving the same again
+    #[test]
+    fn corpus_is_read_safely() {
+        let mut read = 0;
+        for entry in std::fs::read_dir("../test-files/corpus").unwrap() {
+            let path = entry.unwrap().path();
+            let bytes = std::fs::read(&path).unwrap();
+            let name = path.file_name().unwrap().to_str().unwrap().to_string();
+            match Tournament::read_from(&bytes[..]) {
+                Ok(tournament) => {
+                    let mut written = Vec::new();
+                    tournament.write_to(&mut written).unwrap();
+                    assert_eq!(written, bytes, "{}", name);
+                },
+                Err(e) => {
+                    let expected = match name.as_str() {
+                        "asymmetric_result.sts" => matches!(e, TournamentIOError::AsymmetricResult(..)),
+                        "bad_utf8_name.sts" => matches!(e, TournamentIOError::InvalidUtf8(_)),
+                        "duplicate_number.sts" => matches!(e, TournamentIOError::DuplicatePlayerNumber(..)),
+                        "trailing_data.sts" => matches!(e, TournamentIOError::TrailingData(_)),
+                        "truncated.sts" => matches!(e, TournamentIOError::UnexpectedEnd(_)),
+                        "unknown_opponent.sts" => matches!(e, TournamentIOError::UnknownPlayer(..)),
+                        _ => false,
+                    };
+                    assert!(expected, "{}: {}", name, e);
+                    assert!(e.offset().is_some_and(|offset| offset <= bytes.len()), "{}", name);
+                },
+            }
+            read += 1;
+        }
+        assert_eq!(read, 10);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let mut tournament = Tournament::new("Json".to_string(), tagged_players(&["A", "B", "A", "B", "C"]));
+        tournament.set_auditing(true);
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::ForfeitWin).unwrap();
+        tournament.report_match(1, Outcome::IntentionalDraw).unwrap();
+        tournament.finilze_round().unwrap();
+        tournament.drop_player(5).unwrap();
+        tournament.start_round().unwrap();
+        tournament.report_games(1, GameScore::new(1, 1, 1)).unwrap();
+
+        let json = tournament.to_json().unwrap();
+        let read = Tournament::from_json(&json).unwrap();
+        assert_eq!(read, tournament);
+        assert_eq!(read.get_pairings().len(), 2);
+        assert_eq!(read.get_audits().len(), 2);
+        assert!(Tournament::from_json("{}").is_err());
+    }
+
+    #[test]
+    fn rounds_in_progress_are_saved() {
+        let players = (1..=7).map(|n| Player::new(n.to_string(), n)).collect();
+        let mut tournament = Tournament::new("Mid Round".to_string(), players);
+        tournament.set_seed(12);
+        tournament.start_round().unwrap();
+        for idx in 0..3 {
+            tournament.report_match(idx, Outcome::Win).unwrap();
+        }
+        tournament.finilze_round().unwrap();
+        tournament.start_round().unwrap();
+        tournament.report_match(0, Outcome::IntentionalDraw).unwrap();
+        tournament.report_games(1, GameScore::new(2, 1, 0)).unwrap();
+        tournament.swap_seats((2, Colour::White), (2, Colour::Black)).unwrap();
+
+        let mut bytes = Vec::new();
+        tournament.write_to(&mut bytes).unwrap();
+        let mut loaded = Tournament::read_from(&bytes[..]).unwrap();
+        assert_eq!(loaded, tournament);
+        assert_eq!(loaded.get_pairing(1).get_games(), Some(GameScore::new(2, 1, 0)));
+
+        // the round carries on from where it was saved, the bye sits at the last table
+        assert!(loaded.get_pairing(3).is_bye());
+        loaded.report_match(2, Outcome::Loss).unwrap();
+        tournament.report_match(2, Outcome::Loss).unwrap();
+        loaded.finilze_round().unwrap();
+        tournament.finilze_round().unwrap();
+        assert_eq!(loaded, tournament);
+    }
 }
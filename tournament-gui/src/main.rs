@@ -1,14 +1,15 @@
 mod update;
 
-use iced::border::color;
 use iced::keyboard::{Event as KEvent, Modifiers};
-use iced::theme::palette;
 use iced::widget::button::{Status, Style};
-use iced::widget::operation::{focus_next, focus_previous};
-use iced::{keyboard, Background, Color, Element, Length, Subscription, Task, Theme};
-use iced::widget::{button, center, column, opaque, row, stack, text, text_input};
-use tournament_core::swiss::{Outcome, Pairing};
-use tournament_core::{player::Player, tournament::Tournament};
+use iced::{keyboard, Color, Length, Subscription, Theme};
+use iced::widget::{button, center, column, opaque, row, scrollable, stack, text, text_input};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use tournament_core::backup::{Autosave, Backup};
+use tournament_core::swiss::{Colour, GameScore, Outcome, Pairing};
+use tournament_core::{player::Player, tournament::{PairingCheck, Tournament}};
 
 fn main() {
     println!("Hello World!");
@@ -20,17 +21,56 @@ fn main() {
 #[derive(Default)]
 pub(crate) struct TournamentApp {
     active_tab: Tabs,
+    #[allow(dead_code)]
     state: TournamentState,
     tournament: Tournament,
     input_player_name: String,
     input_player_id: String,
     input_player_error: String,
-    dialog_state: Option<DialogStates>
+    dialog_state: Option<DialogStates>,
+    /// result of the last pairing log export
+    log_status: String,
+    /// seat picked in the matches view waiting for a second one to swap with
+    selected_seat: Option<(usize, Colour)>,
+    /// result of the last backup restore
+    save_status: String,
+    /// backups on disk, only read again once another is taken
+    backups: Vec<Backup>,
+    /// when the newest backup in `backups` was taken
+    backups_read: Option<SystemTime>,
+    /// why the last round could not be started
+    round_error: String,
 }
 
+/// the tournament is saved here after every change and picked back up from it on start
+const AUTOSAVE_FILE: &str = "tournament.sts";
+const BACKUPS_KEPT: usize = 10;
+
 impl TournamentApp {
     fn new() -> Self {
-        Self::default()
+        let mut save_status = String::new();
+        let mut tournament = match Tournament::read_from_file(AUTOSAVE_FILE) {
+            Ok(tournament) => tournament,
+            Err(e) => {
+                save_status = format!("Could not load {}: {}", AUTOSAVE_FILE, e);
+                Tournament::default()
+            },
+        };
+        tournament.set_autosave(Some(Autosave::new(AUTOSAVE_FILE).with_backups(BACKUPS_KEPT)));
+
+        let mut app = Self {
+            tournament,
+            save_status,
+            ..Self::default()
+        };
+        app.read_backups();
+        app
+    }
+
+    fn read_backups(&mut self) {
+        let autosave = self.tournament.get_autosave();
+        self.backups = autosave.and_then(|a| a.list_backups().ok()).unwrap_or_default();
+        self.backups_read = autosave.and_then(|a| a.get_last_backup());
     }
 
     fn view(&self) -> iced::Element<'_, TournamentEvent> {
@@ -39,11 +79,17 @@ impl TournamentApp {
                 row![
                     button("Matches").on_press(TournamentEvent::MatchesTab),
                     button("Players").on_press(TournamentEvent::PlayersTab),
+                    button("Pairing Log").on_press(TournamentEvent::LogTab),
+                    button("Saves").on_press(TournamentEvent::SavesTab),
                     button("OtherStuff").on_press(TournamentEvent::OtherStuffTab),
+                    button("Undo").on_press_maybe(self.tournament.get_journal().can_undo().then_some(TournamentEvent::Undo)),
+                    button("Redo").on_press_maybe(self.tournament.get_journal().can_redo().then_some(TournamentEvent::Redo)),
                 ],
                 match self.active_tab {
                     Tabs::Matches => self.matches_tab(),
                     Tabs::Players => self.player_tab_view(),
+                    Tabs::Log => self.log_tab(),
+                    Tabs::Saves => self.saves_tab(),
                     Tabs::OtherStuff => "Other Stuff!".into(),
                 },
                 "I am top",
@@ -72,13 +118,14 @@ impl TournamentApp {
     fn player_tab_view(&self) -> iced::Element<'_, TournamentEvent> {
         // grid of Players
         column![
-            (!self.tournament.get_players().is_empty())
+            (self.tournament.iter_all_players().next().is_some())
                 .then(|| row![
                     text("Name").width(Length::FillPortion(1)),
                     text("Id").width(Length::FillPortion(1)),
                     text("W-L-T").width(Length::FillPortion(1)),
+                    text("Status").width(Length::FillPortion(1)),
                 ]),
-            column(self.tournament.get_players().iter().map(|p| player_view(p))),
+            column(self.tournament.iter_all_players().map(|p| player_view(p, self.tournament.is_dropped(p.get_number())))),
             row![
                 text_input("Player Name", &self.input_player_name).on_input(TournamentEvent::PlayerNameUpdate),
                 text_input("player_id", &self.input_player_id).on_input(TournamentEvent::PlayerIdUpdate),
@@ -90,7 +137,17 @@ impl TournamentApp {
     
     fn matches_tab(&self) -> iced::Element<'_, TournamentEvent> {
         column![
-            button("Start Tournament").on_press(TournamentEvent::MoveTournamentAlong(TournamentState::DuringRound)),
+            row![
+                button("Start Tournament").on_press(TournamentEvent::MoveTournamentAlong(TournamentState::DuringRound)),
+                button("Re-pair Round").on_press(TournamentEvent::RepairRound(false)),
+            ],
+            (!self.round_error.is_empty()).then(|| text(&self.round_error)),
+            // shown so a disputed round can be reproduced later
+            text(format!("Pairing seed: {:016x}", self.tournament.get_seed())),
+            column(pairing_warnings(self.tournament.validate_pairings())),
+            column(self.tournament.get_relaxed_tags().iter().map(|(a, b)| text(format!("Paired despite sharing a tag: {} vs {}", a, b)).into())),
+            // swapping takes back whatever was reported for either table
+            self.selected_seat.is_some().then(|| text("Pick the seat to swap with, results reported on both tables will be cleared")),
             column(
                 self
                     .tournament
@@ -98,19 +155,52 @@ impl TournamentApp {
                     .chunks(2)
                     .enumerate()
                     .map(|(idx, c)| match c {
-                        [a, b] => row![pairing_display(a, idx * 2), pairing_display(b, idx*2 + 1)].into(),
-                        [a] => row![pairing_display(a, idx*2), row![].width(Length::FillPortion(1))].into(),
+                        [a, b] => row![pairing_display(a, idx * 2, self.selected_seat), pairing_display(b, idx*2 + 1, self.selected_seat)].into(),
+                        [a] => row![pairing_display(a, idx*2, self.selected_seat), row![].width(Length::FillPortion(1))].into(),
                         _ => unreachable!()
                     })
             ),
         ].into()     
     }
 
+    fn log_tab(&self) -> iced::Element<'_, TournamentEvent> {
+        let recording = self.tournament.is_auditing();
+        column![
+            row![
+                button(if recording { "Stop Recording" } else { "Record Pairings" }).on_press(TournamentEvent::SetAuditing(!recording)),
+                button("Export").on_press(TournamentEvent::ExportLog),
+            ],
+            (!self.log_status.is_empty()).then(|| text(&self.log_status)),
+            scrollable(text(self.tournament.audit_report())),
+        ].into()
+    }
+
+    fn saves_tab(&self) -> iced::Element<'_, TournamentEvent> {
+        let Some(autosave) = self.tournament.get_autosave() else {
+            return "Not saving".into();
+        };
+
+        column![
+            text(format!("Saving to {}", autosave.get_path().display())),
+            autosave.get_error().map(|e| text(format!("Last save failed: {}", e))),
+            autosave.get_backup_error().map(|e| text(format!("Last backup failed: {}", e))),
+            (!self.save_status.is_empty()).then(|| text(&self.save_status)),
+            column(self.backups.iter().map(backup_view)),
+        ].into()
+    }
+
     fn dialog_view(&self) -> Option<iced::Element<'_, TournamentEvent>> {
        let content = match self.dialog_state.as_ref()? {
            DialogStates::MatchReportState {
                match_index
-           } => report_dialog(self.tournament.get_pairing(*match_index)),
+           } => report_dialog(self.tournament.get_pairing(*match_index), *match_index),
+           DialogStates::ConfirmRepair => column![
+               text("Results have been reported this round, re-pairing will throw them away"),
+               row![
+                   button("Re-pair").on_press(TournamentEvent::RepairRound(true)),
+                   button("Cancel").on_press(TournamentEvent::CloseDialog),
+               ],
+           ].into(),
            _ => todo!()
        };
 
@@ -118,7 +208,7 @@ impl TournamentApp {
     }
 }
 
-fn player_view(player: &Player) -> iced::Element<'_, TournamentEvent> {
+fn player_view(player: &Player, dropped: bool) -> iced::Element<'_, TournamentEvent> {
     row![
         text(player.get_name()).width(Length::FillPortion(1)),
         text(player.get_number()).width(Length::FillPortion(1)),
@@ -126,11 +216,30 @@ fn player_view(player: &Player) -> iced::Element<'_, TournamentEvent> {
             let (wins, ties, losses) =  player.get_record();
             text(format!("{}-{}-{}", wins, ties, losses)).width(Length::FillPortion(1))
         },
+        if dropped {
+            row![
+                text("Dropped").width(Length::FillPortion(1)),
+                button("Undrop").on_press(TournamentEvent::UndropPlayer(player.get_number())),
+            ]
+        } else {
+            row![
+                text("Active").width(Length::FillPortion(1)),
+                button("Drop").on_press(TournamentEvent::DropPlayer(player.get_number())),
+            ]
+        }.width(Length::FillPortion(1)),
     ].into()
 }
 
-fn report_dialog(pairing: &Pairing) -> iced::Element<'_, TournamentEvent> {
+fn report_dialog(pairing: &Pairing, match_index: usize) -> iced::Element<'_, TournamentEvent> {
     let (p1, p2) = pairing.get_players();
+    // game scores from the side of the first player
+    let scores = [
+        GameScore::new(2, 0, 0),
+        GameScore::new(2, 1, 0),
+        GameScore::new(1, 1, 1),
+        GameScore::new(1, 2, 0),
+        GameScore::new(0, 2, 0),
+    ];
     column![
         text(p1.get_name()),
         text(p2.map_or("bye", |p| p.get_name())),
@@ -139,18 +248,37 @@ fn report_dialog(pairing: &Pairing) -> iced::Element<'_, TournamentEvent> {
             button("P2 wins"),
             button("Players Tie"),
         ],
+        row(scores.into_iter().map(|s| {
+            button(text(s.to_string()))
+                .on_press(TournamentEvent::DeclareGames(match_index, s))
+                .into()
+        })),
+        row![
+            button("P2 no show").on_press(TournamentEvent::DeclareMatch(match_index, Outcome::ForfeitWin)),
+            button("P1 no show").on_press(TournamentEvent::DeclareMatch(match_index, Outcome::ForfeitLoss)),
+            button("Intentional Draw").on_press(TournamentEvent::DeclareMatch(match_index, Outcome::IntentionalDraw)),
+            button("Double Loss").on_press(TournamentEvent::DeclareMatch(match_index, Outcome::DoubleLoss)),
+        ],
         row![
             button("Confirm"),
-            button("Close"),
+            button("Close").on_press(TournamentEvent::CloseDialog),
         ],
     ].into()
 }
 
-fn pairing_display(pairing: &Pairing, match_number: usize) -> iced::Element<'_, TournamentEvent> {
+/// a players name, pressing it selects their seat to be swapped with the next one pressed
+fn seat_button(player: &Player, seat: (usize, Colour), selected: Option<(usize, Colour)>) -> iced::widget::Button<'_, TournamentEvent> {
+    let is_selected = selected == Some(seat);
+    button(text(player.get_name()))
+        .style(move |t, s| if is_selected { button::primary(t, s) } else { button::text(t, s) })
+        .on_press(TournamentEvent::SelectSeat(seat.0, seat.1))
+}
+
+fn pairing_display(pairing: &Pairing, match_number: usize, selected: Option<(usize, Colour)>) -> iced::Element<'_, TournamentEvent> {
     let (p1, p2) = pairing.get_players();
     column![
         row![
-            text(p1.get_name()).width(Length::FillPortion(1)),
+            seat_button(p1, (match_number, Colour::White), selected).width(Length::FillPortion(1)),
             button("Winner")
                 .style(|t, s| button_style(t, s, pairing.get_outcome(), true))
                 .on_press(TournamentEvent::OpenMatchDialoge(match_number))
@@ -158,7 +286,7 @@ fn pairing_display(pairing: &Pairing, match_number: usize) -> iced::Element<'_,
         ],
         match p2 {
             Some(p) => row![
-                text(p.get_name()).width(Length::FillPortion(1)),
+                seat_button(p, (match_number, Colour::Black), selected).width(Length::FillPortion(1)),
                 button("Winner")
                     .style(|t, s| button_style(t, s, pairing.get_outcome(), false))
                     .on_press(TournamentEvent::DeclareMatch(match_number, Outcome::Loss))
@@ -170,14 +298,29 @@ fn pairing_display(pairing: &Pairing, match_number: usize) -> iced::Element<'_,
     .into()
 }
 
+fn backup_view<'a>(backup: &Backup) -> iced::Element<'a, TournamentEvent> {
+    let minutes = SystemTime::now().duration_since(backup.time).unwrap_or_default().as_secs() / 60;
+    row![
+        text(format!("{} minutes ago", minutes)).width(Length::FillPortion(1)),
+        button("Restore").on_press(TournamentEvent::RestoreBackup(backup.path.clone())),
+    ].into()
+}
+
+fn pairing_warnings<'a>(check: PairingCheck) -> Vec<iced::Element<'a, TournamentEvent>> {
+    let unpaired = (!check.unpaired.is_empty()).then(|| format!("Not paired: {:?}", check.unpaired));
+    let rematches = check.rematches.iter().map(|(a, b)| format!("Rematch: {} vs {}", a, b));
+    unpaired.into_iter().chain(rematches).map(|w| text(w).into()).collect()
+}
+
 fn button_style(theme: &Theme, _status: Status, outcome: Option<Outcome>, first_player: bool) -> Style {
     let palette = theme.palette();
     Style {
         background: outcome.map(|out| match (out, first_player) {
             // TODO: make nicer colours
-            (Outcome::Win, true) | (Outcome::Loss, false) => iced::Background::Color(Color::from_rgb(0.0, 1.0, 0.0)),
-            (Outcome::Win, false) | (Outcome::Loss, true) => iced::Background::Color(Color::from_rgb(1.0, 0.0, 0.0)),
-            _ => todo!()
+            (Outcome::Win | Outcome::ForfeitWin | Outcome::Bye, true) | (Outcome::Loss | Outcome::ForfeitLoss, false) => iced::Background::Color(Color::from_rgb(0.0, 1.0, 0.0)),
+            (Outcome::Win | Outcome::ForfeitWin, false) | (Outcome::Loss | Outcome::ForfeitLoss | Outcome::DoubleLoss, _) => iced::Background::Color(Color::from_rgb(1.0, 0.0, 0.0)),
+            (Outcome::Tie | Outcome::IntentionalDraw, _) => iced::Background::Color(Color::from_rgb(1.0, 1.0, 0.0)),
+            (Outcome::Bye, false) => unreachable!("byes only have a first player"),
         }).or(Some(iced::Background::Color(palette.primary))),
         text_color: palette.text,
         ..Default::default()
@@ -188,14 +331,29 @@ fn button_style(theme: &Theme, _status: Status, outcome: Option<Outcome>, first_
 pub(crate) enum TournamentEvent {
     MatchesTab,
     PlayersTab,
+    LogTab,
+    SavesTab,
     OtherStuffTab,
     PlayerNameUpdate(String),
     PlayerIdUpdate(String),
     MoveTournamentAlong(TournamentState),
     /// used to declare winners where usize is the match number
     DeclareMatch(usize, Outcome),
+    /// used to declare every game of the match where usize is the match number
+    DeclareGames(usize, GameScore),
     OpenMatchDialoge(usize),
     AddPlayer,
+    DropPlayer(u16),
+    UndropPlayer(u16),
+    /// pairs the current round again, the bool confirms throwing away reported results
+    RepairRound(bool),
+    SelectSeat(usize, Colour),
+    CloseDialog,
+    SetAuditing(bool),
+    ExportLog,
+    RestoreBackup(PathBuf),
+    Undo,
+    Redo,
     TabPress,
     ShiftTabPress,
     NonSense
@@ -206,9 +364,12 @@ pub(crate) enum Tabs {
     #[default]
     Matches,
     Players,
+    Log,
+    Saves,
     OtherStuff,
 }
 
+#[allow(dead_code)]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum TournamentState {
     #[default]
@@ -218,6 +379,7 @@ pub(crate) enum TournamentState {
     Fin,
 }
 
+#[allow(dead_code)]
 enum DialogStates {
     // only usize bc we can get what we need from pairings
     MatchReportState {
@@ -227,5 +389,6 @@ enum DialogStates {
         name: String,
         id: String,
         error_box: String,
-    }
+    },
+    ConfirmRepair,
 }
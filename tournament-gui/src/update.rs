@@ -4,7 +4,7 @@ use iced::Task;
 use iced::widget::operation::{focus_next, focus_previous};
 
 use tournament_core::player::Player;
-use tournament_core::swiss::Outcome;
+use tournament_core::tournament::{Tournament, TournamentError};
 
 impl TournamentApp {
     pub(crate) fn update(&mut self, message: TournamentEvent) -> Task<TournamentEvent> {
@@ -12,21 +12,104 @@ impl TournamentApp {
         match message {
             TournamentEvent::MatchesTab => self.active_tab = Tabs::Matches,
             TournamentEvent::PlayersTab => self.active_tab = Tabs::Players,
+            TournamentEvent::LogTab => self.active_tab = Tabs::Log,
+            TournamentEvent::SavesTab => self.active_tab = Tabs::Saves,
             TournamentEvent::OtherStuffTab => self.active_tab = Tabs::OtherStuff,
             TournamentEvent::PlayerIdUpdate(v) => self.input_player_id = v,
             TournamentEvent::PlayerNameUpdate(v) => self.input_player_name = v,
             TournamentEvent::AddPlayer => self.add_player(),
-            TournamentEvent::DeclareMatch(idx, res) => self.tournament.report_match(idx, res).unwrap(),
+            TournamentEvent::DropPlayer(number) => {
+                if let Err(e) = self.tournament.drop_player(number) {
+                    self.input_player_error = e.to_string();
+                }
+            },
+            TournamentEvent::UndropPlayer(number) => {
+                if let Err(e) = self.tournament.undrop_player(number) {
+                    self.input_player_error = e.to_string();
+                }
+            },
+            TournamentEvent::DeclareMatch(idx, res) => {
+                if self.tournament.report_match(idx, res).is_ok() {
+                    self.dialog_state = None;
+                }
+            },
+            TournamentEvent::DeclareGames(idx, games) => {
+                if self.tournament.report_games(idx, games).is_ok() {
+                    self.dialog_state = None;
+                }
+            },
+            TournamentEvent::RepairRound(discard_results) => match self.tournament.repair_round(discard_results) {
+                Ok(()) => {
+                    self.dialog_state = None;
+                    self.round_error.clear();
+                },
+                Err(TournamentError::ResultsReported) => self.dialog_state = Some(crate::DialogStates::ConfirmRepair),
+                Err(e) => {
+                    self.dialog_state = None;
+                    self.round_error = e.to_string();
+                },
+            },
+            TournamentEvent::CloseDialog => self.dialog_state = None,
+            TournamentEvent::SetAuditing(auditing) => self.tournament.set_auditing(auditing),
+            TournamentEvent::ExportLog => {
+                self.log_status = match std::fs::write("pairing-log.txt", self.tournament.audit_report()) {
+                    Ok(()) => "Saved to pairing-log.txt".to_string(),
+                    Err(e) => e.to_string(),
+                };
+            },
+            TournamentEvent::RestoreBackup(path) => match Tournament::read_from_file(&path) {
+                Ok(mut tournament) => {
+                    // keeps saving to the same place, setting the autosave writes the restored
+                    // state over the save straight away so a restart keeps it
+                    tournament.set_autosave(self.tournament.get_autosave().cloned());
+                    self.save_status = match tournament.get_autosave().and_then(|a| a.get_error()) {
+                        Some(e) => format!("Restored {} but could not save it: {}", path.display(), e),
+                        None => format!("Restored {}", path.display()),
+                    };
+                    self.tournament = tournament;
+                    self.dialog_state = None;
+                    self.selected_seat = None;
+                },
+                Err(e) => self.save_status = e.to_string(),
+            },
+            TournamentEvent::SelectSeat(match_idx, colour) => match self.selected_seat.take() {
+                Some(selected) => {
+                    self.round_error = match self.tournament.swap_seats(selected, (match_idx, colour)) {
+                        Ok(()) => String::new(),
+                        Err(e) => e.to_string(),
+                    };
+                },
+                None => self.selected_seat = Some((match_idx, colour)),
+            },
+            // the match a dialog was open for may not exist any more
+            TournamentEvent::Undo => {
+                if self.tournament.undo().is_ok() {
+                    self.dialog_state = None;
+                }
+            },
+            TournamentEvent::Redo => {
+                if self.tournament.redo().is_ok() {
+                    self.dialog_state = None;
+                }
+            },
             TournamentEvent::OpenMatchDialoge(idx) => self.dialog_state = Some(crate::DialogStates::MatchReportState { match_index: idx }),
             TournamentEvent::TabPress => final_task = focus_next(),
             TournamentEvent::ShiftTabPress => final_task = focus_previous(),
             TournamentEvent::MoveTournamentAlong(TournamentState::DuringRound) => {
-                self.tournament.start_round().unwrap();
+                self.round_error = match self.tournament.start_round() {
+                    Ok(()) => String::new(),
+                    Err(e) => e.to_string(),
+                };
             }
             TournamentEvent::NonSense => {},
             _ => println!("unhandled :3"),
         }
 
+        // every change is saved, the backups are only read again when that took another
+        if self.tournament.get_autosave().and_then(|a| a.get_last_backup()) != self.backups_read {
+            self.read_backups();
+        }
+
         final_task
     }
     
@@ -40,7 +123,7 @@ impl TournamentApp {
             }
         };
 
-        if let Some(id) = self.tournament.get_players().iter().map(|p| p.get_number()).find(|&id| id == player_id) {
+        if let Some(id) = self.tournament.iter_all_players().map(|p| p.get_number()).find(|&id| id == player_id) {
             self.input_player_error = format!("Player ID of {} is not unique", id);
             return;
         }